@@ -0,0 +1,354 @@
+//!
+//! Keyword, organism and full-text search over the PLACE annotations.
+//!
+//! The `kw`, `os` and `de` fields of every element are split into
+//! lower-case tokens and kept in an inverted index, so a query like
+//! `kw:"light regulation" AND os:arabidopsis` is answered with set operations.
+//!
+//! Query syntax:
+//!
+//! - a term is a word or a `"quoted phrase"`, all of its tokens must be in the field,
+//!   in a single keyword for `kw` since an element has several of them,
+//! - a term may be prefixed by its field: `kw:`, `os:`, `de:`, otherwise any field matches,
+//! - terms are combined with `AND` / `OR` (case-insensitive), `AND` binds tighter,
+//!   two terms without an operator are combined with `AND`,
+//! - parentheses group sub-queries.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::error::Error;
+use crate::place_desc::SeqDesc;
+
+/// The annotation field a term is searched in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// Keywords (`KW`).
+    Keyword,
+    /// Organism source (`OS`).
+    Organism,
+    /// Description (`DE`).
+    Description,
+    /// Any of the fields above.
+    Any,
+}
+
+/// A boolean query over the annotations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnnotationQuery {
+    /// All tokens of `text` are in `field`.
+    Term { field: Field, text: String },
+    /// Every sub-query matches.
+    And(Vec<AnnotationQuery>),
+    /// At least one sub-query matches.
+    Or(Vec<AnnotationQuery>),
+}
+
+impl AnnotationQuery {
+    /// Elements with a keyword containing all tokens of `text`.
+    pub fn keyword(text: &str) -> Self {
+        Self::term(Field::Keyword, text)
+    }
+
+    /// Elements from an organism matching all tokens of `text`.
+    pub fn organism(text: &str) -> Self {
+        Self::term(Field::Organism, text)
+    }
+
+    /// Elements whose description contains all tokens of `text`.
+    pub fn description(text: &str) -> Self {
+        Self::term(Field::Description, text)
+    }
+
+    /// Elements with all tokens of `text` in one of the fields.
+    pub fn any(text: &str) -> Self {
+        Self::term(Field::Any, text)
+    }
+
+    fn term(field: Field, text: &str) -> Self {
+        Self::Term {
+            field,
+            text: text.to_string(),
+        }
+    }
+
+    /// Both queries match.
+    pub fn and(self, other: Self) -> Self {
+        Self::And(vec![self, other])
+    }
+
+    /// One of the queries matches.
+    pub fn or(self, other: Self) -> Self {
+        Self::Or(vec![self, other])
+    }
+
+    /// Parse a query written in the syntax described in the module.
+    pub fn parse(query: &str) -> Result<Self, Error> {
+        Self::parse_lexemes(query).map_err(Error::InvalidQuery)
+    }
+
+    fn parse_lexemes(query: &str) -> Result<Self, String> {
+        let tokens = lex(query)?;
+        let mut parser = QueryParser { tokens, pos: 0 };
+        let parsed = parser.parse_or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(format!(
+                "Unexpected {:?} in annotation query",
+                parser.tokens[parser.pos]
+            ));
+        }
+        Ok(parsed)
+    }
+}
+
+/// An inverted index over the `kw`, `os` and `de` fields of the elements.
+///
+/// The positions returned by a search point into the slice the index was built from,
+/// `SeqBuilder::all` for the index of a `Database`.
+#[derive(Debug, Clone, Default)]
+pub struct AnnotationIndex {
+    // token -> keywords, numbered over all the elements
+    keyword: HashMap<String, BTreeSet<usize>>,
+    // keyword -> position of its element
+    keyword_owner: Vec<usize>,
+    organism: HashMap<String, BTreeSet<usize>>,
+    description: HashMap<String, BTreeSet<usize>>,
+}
+
+impl AnnotationIndex {
+    /// Index the annotations of the elements.
+    pub fn new(descs: &[SeqDesc]) -> Self {
+        let mut index = Self::default();
+        for (p, desc) in descs.iter().enumerate() {
+            for kw in &desc.kw {
+                insert_tokens(&mut index.keyword, kw, index.keyword_owner.len());
+                index.keyword_owner.push(p);
+            }
+            insert_tokens(&mut index.organism, &desc.os, p);
+            insert_tokens(&mut index.description, &desc.de, p);
+        }
+        index
+    }
+
+    /// The positions of the matching elements, in ascending order.
+    pub fn search(&self, query: &AnnotationQuery) -> Vec<usize> {
+        self.eval(query).into_iter().collect()
+    }
+
+    fn eval(&self, query: &AnnotationQuery) -> BTreeSet<usize> {
+        match query {
+            AnnotationQuery::Term { field, text } => match field {
+                Field::Keyword => self.keyword_matches(text),
+                Field::Organism => term_matches(&self.organism, text),
+                Field::Description => term_matches(&self.description, text),
+                Field::Any => {
+                    let mut res = self.keyword_matches(text);
+                    res.extend(term_matches(&self.organism, text));
+                    res.extend(term_matches(&self.description, text));
+                    res
+                }
+            },
+            AnnotationQuery::And(queries) => {
+                let mut sets = queries.iter().map(|q| self.eval(q));
+                let first = sets.next().unwrap_or_default();
+                sets.fold(first, |acc, set| acc.intersection(&set).copied().collect())
+            }
+            AnnotationQuery::Or(queries) => queries.iter().flat_map(|q| self.eval(q)).collect(),
+        }
+    }
+
+    /// Elements with a keyword having all the tokens of `text`.
+    fn keyword_matches(&self, text: &str) -> BTreeSet<usize> {
+        term_matches(&self.keyword, text)
+            .into_iter()
+            .map(|kw| self.keyword_owner[kw])
+            .collect()
+    }
+}
+
+/// Split a text into lower-case tokens.
+///
+/// Tokens are made of letters, digits and inner `-`, e.g. `ASF-1`.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '-'))
+        .map(|t| t.trim_matches('-'))
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+}
+
+/// Whether all the tokens of `text` are in `field`, without an index.
+pub(crate) fn contains_tokens(field: &str, text: &str) -> bool {
+    let tokens: BTreeSet<String> = tokenize(field).collect();
+    tokenize(text).all(|t| tokens.contains(&t))
+}
+
+fn insert_tokens(map: &mut HashMap<String, BTreeSet<usize>>, text: &str, p: usize) {
+    for token in tokenize(text) {
+        map.entry(token).or_default().insert(p);
+    }
+}
+
+/// Elements having all the tokens of `text` in one field.
+fn term_matches(map: &HashMap<String, BTreeSet<usize>>, text: &str) -> BTreeSet<usize> {
+    let mut res: Option<BTreeSet<usize>> = None;
+    for token in tokenize(text) {
+        let set = map.get(&token).cloned().unwrap_or_default();
+        res = Some(match res {
+            Some(acc) => acc.intersection(&set).copied().collect(),
+            None => set,
+        });
+    }
+    res.unwrap_or_default()
+}
+
+/// A lexical token of the query syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Lexeme {
+    Open,
+    Close,
+    And,
+    Or,
+    Term(Field, String),
+}
+
+fn lex(query: &str) -> Result<Vec<Lexeme>, String> {
+    let mut lexemes = vec![];
+    let mut chars = query.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c == '(' || c == ')' {
+            chars.next();
+            lexemes.push(if c == '(' {
+                Lexeme::Open
+            } else {
+                Lexeme::Close
+            });
+            continue;
+        }
+
+        // A word, which may be a field prefix followed by a phrase
+        let mut word = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
+                break;
+            }
+            word.push(c);
+            chars.next();
+        }
+
+        let (field, mut text) = match word.split_once(':') {
+            Some((prefix, rest)) => {
+                let field = match prefix.to_lowercase().as_str() {
+                    "kw" => Field::Keyword,
+                    "os" => Field::Organism,
+                    "de" => Field::Description,
+                    _ => return Err(format!("Unknown field {:?} in annotation query", prefix)),
+                };
+                (field, rest.to_string())
+            }
+            None => (Field::Any, word),
+        };
+
+        if chars.peek() == Some(&'"') && text.is_empty() {
+            chars.next();
+            let mut closed = false;
+            for c in chars.by_ref() {
+                if c == '"' {
+                    closed = true;
+                    break;
+                }
+                text.push(c);
+            }
+            if !closed {
+                return Err("Unclosed quote in annotation query".to_string());
+            }
+        } else if field == Field::Any {
+            match text.to_uppercase().as_str() {
+                "AND" => {
+                    lexemes.push(Lexeme::And);
+                    continue;
+                }
+                "OR" => {
+                    lexemes.push(Lexeme::Or);
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        if text.is_empty() {
+            return Err("Empty term in annotation query".to_string());
+        }
+        lexemes.push(Lexeme::Term(field, text));
+    }
+
+    Ok(lexemes)
+}
+
+/// Recursive descent over the lexemes: `or := and (OR and)*`, `and := atom (AND? atom)*`.
+struct QueryParser {
+    tokens: Vec<Lexeme>,
+    pos: usize,
+}
+
+impl QueryParser {
+    fn peek(&self) -> Option<&Lexeme> {
+        self.tokens.get(self.pos)
+    }
+
+    fn parse_or(&mut self) -> Result<AnnotationQuery, String> {
+        let mut queries = vec![self.parse_and()?];
+        while self.peek() == Some(&Lexeme::Or) {
+            self.pos += 1;
+            queries.push(self.parse_and()?);
+        }
+        Ok(flatten(queries, AnnotationQuery::Or))
+    }
+
+    fn parse_and(&mut self) -> Result<AnnotationQuery, String> {
+        let mut queries = vec![self.parse_atom()?];
+        loop {
+            match self.peek() {
+                Some(Lexeme::And) => {
+                    self.pos += 1;
+                    queries.push(self.parse_atom()?);
+                }
+                Some(Lexeme::Open) | Some(Lexeme::Term(..)) => queries.push(self.parse_atom()?),
+                _ => break,
+            }
+        }
+        Ok(flatten(queries, AnnotationQuery::And))
+    }
+
+    fn parse_atom(&mut self) -> Result<AnnotationQuery, String> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        match token {
+            Some(Lexeme::Term(field, text)) => Ok(AnnotationQuery::Term { field, text }),
+            Some(Lexeme::Open) => {
+                let query = self.parse_or()?;
+                if self.peek() != Some(&Lexeme::Close) {
+                    return Err("Missing ')' in annotation query".to_string());
+                }
+                self.pos += 1;
+                Ok(query)
+            }
+            Some(other) => Err(format!("Unexpected {:?} in annotation query", other)),
+            None => Err("Unexpected end of annotation query".to_string()),
+        }
+    }
+}
+
+fn flatten(
+    mut queries: Vec<AnnotationQuery>,
+    combine: fn(Vec<AnnotationQuery>) -> AnnotationQuery,
+) -> AnnotationQuery {
+    if queries.len() == 1 {
+        queries.remove(0)
+    } else {
+        combine(queries)
+    }
+}
@@ -0,0 +1,373 @@
+//!
+//! Background models of the queries, to tell the expected hits from the meaningful ones.
+//!
+//! Each query is compared with random sequences of the same length, drawn from:
+//!
+//! - `Shuffle`: the query shuffled with its dinucleotides kept (Altschul-Erickson),
+//!   so its composition and its CpG / dinucleotide biases stay the same,
+//! - `Markov`: a k-th order Markov model, trained on the queries or on a genome.
+//!
+//! The random sequences are searched like the queries, through `Search`,
+//! and give the expected count of each element and an empirical p-value,
+//! `(1 + permutations with as many hits or more) / (1 + permutations)`.
+//! The same seed always gives the same sequences.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::BufRead;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+use crate::error::Error;
+use crate::io::{FastaChunks, RecordDesc, SearchResult};
+use crate::place_search::{Search, SearchOptions};
+
+/// The highest order of a `MarkovModel`, its table has `4^(order + 1)` counts.
+pub const MAX_MARKOV_ORDER: usize = 8;
+
+/// Bases of a genome read at once by `MarkovModel::train_reader`.
+const TRAIN_CHUNK_LEN: usize = 1 << 20;
+
+/// A k-th order Markov model of DNA, `A`, `C`, `G` and `T` only.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkovModel {
+    order: usize,
+    // counts of each base after each k-mer, indexed by `kmer * 4 + base`
+    counts: Vec<u64>,
+}
+
+impl MarkovModel {
+    /// An untrained model, every base equally likely.
+    ///
+    /// An order above `MAX_MARKOV_ORDER` is an error.
+    pub fn new(order: usize) -> Result<Self, Error> {
+        if order > MAX_MARKOV_ORDER {
+            return Err(Error::InvalidOptions(format!(
+                "the Markov order is at most {}, found {}",
+                MAX_MARKOV_ORDER, order
+            )));
+        }
+        Ok(Self {
+            order,
+            counts: vec![0; 4usize.pow(order as u32 + 1)],
+        })
+    }
+
+    /// Train a model on sequences, the windows with other bases than `ACGT` are skipped.
+    pub fn train(records: &[RecordDesc], order: usize) -> Result<Self, Error> {
+        let mut model = Self::new(order)?;
+        for record in records {
+            model.count(record.seq_bytes(), record.len());
+        }
+        Ok(model)
+    }
+
+    /// Train a model on a FASTA stream of any size, e.g. a genome, with bounded memory.
+    pub fn train_reader<R: BufRead>(reader: R, order: usize) -> Result<Self, Error> {
+        let mut model = Self::new(order)?;
+        for chunk in FastaChunks::new(reader, TRAIN_CHUNK_LEN, model.order) {
+            let chunk = chunk?;
+            // The windows starting in the overlap are counted with the next chunk
+            model.count(chunk.record.seq_bytes(), chunk.core_len);
+        }
+        Ok(model)
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Count the `order + 1` windows of a sequence starting before `starts`.
+    fn count(&mut self, seq: &[u8], starts: usize) {
+        let window = self.order + 1;
+        if seq.len() < window {
+            return;
+        }
+        for bases in seq.windows(window).take(starts) {
+            if let Some(index) = kmer_index(bases) {
+                self.counts[index] += 1;
+            }
+        }
+    }
+
+    /// Draw a sequence of `len` bases.
+    fn generate(&self, len: usize, rng: &mut ChaCha8Rng) -> Vec<u8> {
+        let mut seq: Vec<u8> = Vec::with_capacity(len);
+        let contexts = self.counts.len() / 4;
+
+        // The first bases are drawn from the k-mer frequencies
+        let kmers: Vec<u64> = (0..contexts)
+            .map(|kmer| self.counts[kmer * 4..kmer * 4 + 4].iter().sum::<u64>() + 1)
+            .collect();
+        let mut kmer = draw(&kmers, rng);
+        for shift in (0..self.order).rev() {
+            seq.push(BASES[(kmer >> (2 * shift)) & 3]);
+        }
+        seq.truncate(len);
+
+        // Then each base from the k-mer before it, with a pseudocount of 1
+        while seq.len() < len {
+            let weights: Vec<u64> = self.counts[kmer * 4..kmer * 4 + 4]
+                .iter()
+                .map(|c| c + 1)
+                .collect();
+            let base = draw(&weights, rng);
+            seq.push(BASES[base]);
+            kmer = (kmer * 4 + base) % contexts;
+        }
+        seq
+    }
+}
+
+const BASES: [u8; 4] = [b'A', b'C', b'G', b'T'];
+
+/// The index of a k-mer of `ACGT` in a table of `4^k` entries.
+fn kmer_index(bases: &[u8]) -> Option<usize> {
+    bases.iter().try_fold(0usize, |index, &base| {
+        let code = match base {
+            b'A' => 0,
+            b'C' => 1,
+            b'G' => 2,
+            b'T' => 3,
+            _ => return None,
+        };
+        Some(index * 4 + code)
+    })
+}
+
+/// Draw an index with probabilities proportional to `weights`.
+fn draw(weights: &[u64], rng: &mut ChaCha8Rng) -> usize {
+    let total: u64 = weights.iter().sum();
+    let mut pick = rng.gen_range(0..total);
+    for (index, &weight) in weights.iter().enumerate() {
+        if pick < weight {
+            return index;
+        }
+        pick -= weight;
+    }
+    weights.len() - 1
+}
+
+/// Shuffle a sequence keeping the count of each dinucleotide, and its first and last base.
+///
+/// Any byte is a symbol, so `N`s stay with their neighbours.
+pub fn dinucleotide_shuffle(seq: &[u8], seed: u64) -> Vec<u8> {
+    shuffle_with(seq, &mut ChaCha8Rng::seed_from_u64(seed))
+}
+
+/// The Altschul-Erickson shuffle: a random Eulerian walk of the dinucleotide graph.
+fn shuffle_with(seq: &[u8], rng: &mut ChaCha8Rng) -> Vec<u8> {
+    if seq.len() < 3 {
+        return seq.to_vec();
+    }
+    let mut edges: HashMap<u8, Vec<u8>> = HashMap::new();
+    for pair in seq.windows(2) {
+        edges.entry(pair[0]).or_default().push(pair[1]);
+    }
+    let first = seq[0];
+    let last = seq[seq.len() - 1];
+
+    // The last edge out of each vertex, they must form a tree rooted at the last base
+    // for the walk to use every edge
+    let mut vertices: Vec<u8> = edges.keys().copied().filter(|&v| v != last).collect();
+    vertices.sort_unstable();
+    let last_edges: HashMap<u8, usize> = loop {
+        let chosen: HashMap<u8, usize> = vertices
+            .iter()
+            .map(|v| (*v, rng.gen_range(0..edges[v].len())))
+            .collect();
+        let reaches_last = vertices.iter().all(|&start| {
+            let mut vertex = start;
+            for _ in 0..=vertices.len() {
+                if vertex == last {
+                    return true;
+                }
+                vertex = edges[&vertex][chosen[&vertex]];
+            }
+            vertex == last
+        });
+        if reaches_last {
+            break chosen;
+        }
+    };
+
+    // Shuffle the other edges, the last one goes at the end
+    for vertex in &vertices {
+        let out = edges.get_mut(vertex).unwrap();
+        let last_edge = out.swap_remove(last_edges[vertex]);
+        out.shuffle(rng);
+        out.push(last_edge);
+    }
+    if let Some(out) = edges.get_mut(&last) {
+        out.shuffle(rng);
+    }
+
+    // Walk from the first base, taking the edges in order
+    let mut next: HashMap<u8, usize> = HashMap::new();
+    let mut shuffled = Vec::with_capacity(seq.len());
+    let mut vertex = first;
+    shuffled.push(vertex);
+    while shuffled.len() < seq.len() {
+        let used = next.entry(vertex).or_insert(0);
+        vertex = edges[&vertex][*used];
+        *used += 1;
+        shuffled.push(vertex);
+    }
+    shuffled
+}
+
+/// How the random sequences are drawn.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum BackgroundModel {
+    /// Dinucleotide-preserving shuffles of each query.
+    #[default]
+    Shuffle,
+    /// Sequences drawn from a Markov model.
+    Markov(MarkovModel),
+}
+
+/// A background model, with the number of random sequences per query and their seed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Background {
+    pub model: BackgroundModel,
+    pub permutations: usize,
+    pub seed: u64,
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self {
+            model: BackgroundModel::default(),
+            permutations: 100,
+            seed: 1,
+        }
+    }
+}
+
+impl Background {
+    /// The random sequences of a query, named `id:perm<i>`.
+    ///
+    /// The seed of each query comes from its index, so a query always gets the same ones.
+    pub fn permutations_of(&self, record: &RecordDesc, index: usize) -> Vec<RecordDesc> {
+        let seed = self.seed ^ (index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        (0..self.permutations)
+            .map(|i| {
+                let seq = match &self.model {
+                    BackgroundModel::Shuffle => shuffle_with(record.seq_bytes(), &mut rng),
+                    BackgroundModel::Markov(model) => model.generate(record.len(), &mut rng),
+                };
+                RecordDesc::from_bytes(&format!("{}:perm{}", record.id(), i), &seq)
+            })
+            .collect()
+    }
+}
+
+/// The hits of an element in a query, against the background.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElementExpectation {
+    pub query_id: String,
+    pub element_id: String,
+    /// Hits in the query, 0 when only its random sequences have some.
+    pub observed: usize,
+    /// Mean hits in the random sequences.
+    pub expected: f64,
+    /// Share of random sequences with at least as many hits, see the module doc.
+    pub p_value: f64,
+}
+
+/// The expected hits of the elements found in the queries or their random sequences.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Expectations {
+    /// Random sequences per query.
+    pub permutations: usize,
+    /// By query, then by increasing p-value.
+    pub elements: Vec<ElementExpectation>,
+}
+
+/// The expected hits of the elements found in each query or its random sequences.
+///
+/// The queries and their random sequences are searched with the same `options`.
+/// An element hitting only the random sequences of a query is reported with
+/// no observed hits, so the depleted elements show up next to the enriched ones.
+pub fn expected_counts(
+    db: &Database,
+    records: &[RecordDesc],
+    options: &SearchOptions,
+    background: &Background,
+) -> Result<Expectations, Error> {
+    let observed = Search::search_elements_with(db, records, options)?;
+    let permutations = background.permutations;
+    let mut elements = vec![];
+
+    for (index, (record, result)) in records.iter().zip(&observed).enumerate() {
+        let counts = element_counts(result);
+        let random = background.permutations_of(record, index);
+        let random_results = Search::search_elements_with(db, &random, options)?;
+        let random_counts: Vec<HashMap<&str, usize>> =
+            random_results.iter().map(element_counts).collect();
+
+        // The elements missing from the query but expected in it are kept too
+        let mut seen: HashSet<&str> = counts.keys().copied().collect();
+        seen.extend(random_counts.iter().flat_map(|c| c.keys().copied()));
+
+        let mut found: Vec<ElementExpectation> = seen
+            .into_iter()
+            .map(|element| {
+                let observed = *counts.get(element).unwrap_or(&0);
+                let in_random = random_counts.iter().map(|c| *c.get(element).unwrap_or(&0));
+                let total: usize = in_random.clone().sum();
+                let as_many = in_random.filter(|&n| n >= observed).count();
+                ElementExpectation {
+                    query_id: record.id().to_string(),
+                    element_id: element.to_string(),
+                    observed,
+                    expected: total as f64 / permutations.max(1) as f64,
+                    p_value: (1 + as_many) as f64 / (1 + permutations) as f64,
+                }
+            })
+            .collect();
+        found.sort_by(|a, b| {
+            a.p_value
+                .total_cmp(&b.p_value)
+                .then_with(|| a.element_id.cmp(&b.element_id))
+        });
+        elements.extend(found);
+    }
+
+    Ok(Expectations {
+        permutations,
+        elements,
+    })
+}
+
+/// The hits of each element in a result.
+fn element_counts<'a>(result: &SearchResult<'a>) -> HashMap<&'a str, usize> {
+    let mut counts = HashMap::new();
+    for desc in &result.search_descs {
+        *counts.entry(desc.e_id).or_insert(0) += 1;
+    }
+    counts
+}
+
+impl fmt::Display for Expectations {
+    /// A TSV table, with a header line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Query ID\tElement ID\tObserved\tExpected\tPermutations\tP-value"
+        )?;
+        for e in &self.elements {
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{:.3}\t{}\t{:.3e}",
+                e.query_id, e.element_id, e.observed, e.expected, self.permutations, e.p_value
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+//!
+//! Strands and coordinates of the hits on the query.
+//!
+//! A hit is stored 0-based half-open, `[start, end)`, which is what the matchers give.
+//! The writers convert it to the system of their format:
+//!
+//! | System                           | first base | `ACGT` in `xxACGT` |
+//! |----------------------------------|------------|--------------------|
+//! | `ZeroBasedHalfOpen` (BED)        | 0          | 2..6               |
+//! | `OneBasedClosed` (GFF3, TSV)     | 1          | 3..6               |
+//!
+//! Hits on the minus strand have the same coordinates as on the plus strand,
+//! they're the forward-strand span of the reverse-complemented element.
+//!
+//! Positions can also be given relative to an anchor of the query, its TSS or ATG,
+//! the way promoter elements are usually reported: the anchor is `+1`,
+//! the base before it `-1`, and there's no position 0.
+//!
+//! A query cut from a genome can carry its `SourceRegion`,
+//! parsed from its FASTA header, to lift the hits back to the genome.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::matcher::PatternStrand;
+
+/// The strand of the query a hit is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Strand {
+    #[serde(rename = "+")]
+    Plus,
+    #[serde(rename = "-")]
+    Minus,
+}
+
+impl Strand {
+    pub fn as_char(self) -> char {
+        match self {
+            Strand::Plus => '+',
+            Strand::Minus => '-',
+        }
+    }
+}
+
+impl fmt::Display for Strand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_char())
+    }
+}
+
+impl From<PatternStrand> for Strand {
+    /// The element itself is on the plus strand, its reverse complement on the minus one.
+    fn from(strand: PatternStrand) -> Self {
+        match strand {
+            PatternStrand::Forward => Strand::Plus,
+            PatternStrand::Reverse => Strand::Minus,
+        }
+    }
+}
+
+/// A convention to write coordinates in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinateSystem {
+    /// The first base is 0, the end is excluded (BED).
+    ZeroBasedHalfOpen,
+    /// The first base is 1, the end is included (GFF3, SAM, VCF).
+    #[default]
+    OneBasedClosed,
+}
+
+impl CoordinateSystem {
+    /// A 0-based position in this system.
+    pub fn position(self, position: usize) -> usize {
+        match self {
+            CoordinateSystem::ZeroBasedHalfOpen => position,
+            CoordinateSystem::OneBasedClosed => position + 1,
+        }
+    }
+}
+
+impl fmt::Display for CoordinateSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoordinateSystem::ZeroBasedHalfOpen => write!(f, "0-based half-open"),
+            CoordinateSystem::OneBasedClosed => write!(f, "1-based closed"),
+        }
+    }
+}
+
+/// A span of the query, kept 0-based half-open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coordinates {
+    start: usize,
+    end: usize,
+}
+
+impl Coordinates {
+    /// A span from 0-based half-open coordinates, `[start, end)`.
+    ///
+    /// An end before the start gives an empty span at `start`.
+    pub fn from_zero_based(start: usize, end: usize) -> Self {
+        Self {
+            start,
+            end: end.max(start),
+        }
+    }
+
+    /// A span from 1-based closed coordinates, `[start, end]`.
+    ///
+    /// A start of 0 is read as 1.
+    pub fn from_one_based(start: usize, end: usize) -> Self {
+        Self::from_zero_based(start.saturating_sub(1), end)
+    }
+
+    /// The 0-based first base.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// The 0-based base after the last one.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// `(start, end)`, 0-based half-open.
+    pub fn zero_based_half_open(&self) -> (usize, usize) {
+        (self.start, self.end)
+    }
+
+    /// `(start, end)`, 1-based closed.
+    pub fn one_based_closed(&self) -> (usize, usize) {
+        (self.start + 1, self.end)
+    }
+
+    /// `(start, end)` in a coordinate system.
+    pub fn in_system(&self, system: CoordinateSystem) -> (usize, usize) {
+        match system {
+            CoordinateSystem::ZeroBasedHalfOpen => self.zero_based_half_open(),
+            CoordinateSystem::OneBasedClosed => self.one_based_closed(),
+        }
+    }
+
+    /// The same span moved `offset` bases forward.
+    pub fn shift(&self, offset: usize) -> Self {
+        Self::from_zero_based(self.start + offset, self.end + offset)
+    }
+
+    /// The first and the last base relative to the anchor at the 0-based `anchor`.
+    pub fn relative_to(&self, anchor: isize) -> (isize, isize) {
+        (
+            relative_position(self.start, anchor),
+            relative_position(self.end.saturating_sub(1).max(self.start), anchor),
+        )
+    }
+}
+
+/// A 0-based position relative to the anchor at the 0-based `anchor`, without 0.
+///
+/// The anchor may be outside the sequence, e.g. right after the end of an upstream region.
+pub fn relative_position(position: usize, anchor: isize) -> isize {
+    relative_offset(position as isize - anchor)
+}
+
+/// A 0-based offset from the anchor, relative to it without 0: `-1` before it, `+1` at it.
+pub fn relative_offset(offset: isize) -> isize {
+    if offset >= 0 { offset + 1 } else { offset }
+}
+
+/// How a region of a contig is written in a FASTA header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RegionConvention {
+    /// `samtools faidx`: `chr1:1000-3000`, 1-based closed, `/rc` on the minus strand.
+    /// A `(+)` / `(-)` suffix is read too, as written by `promoters::write_fasta`.
+    #[default]
+    Samtools,
+    /// `bedtools getfasta`: `chr1:999-3000`, 0-based half-open,
+    /// `(+)` / `(-)` with `-s` and a `name::` prefix with `-name`.
+    Bedtools,
+}
+
+/// The region of a contig a query sequence was cut from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceRegion {
+    pub contig: String,
+    pub coords: Coordinates,
+    /// `Minus` when the query is the reverse complement of the region.
+    pub strand: Strand,
+}
+
+impl SourceRegion {
+    /// Parse a region written in a FASTA header, e.g. `chr1:1000-3000(-)`.
+    pub fn parse(text: &str, convention: RegionConvention) -> Option<Self> {
+        let text = match text.rsplit_once("::") {
+            Some((_, region)) => region,
+            None => text,
+        };
+        let (text, strand) = if let Some(text) = text.strip_suffix("(-)") {
+            (text, Strand::Minus)
+        } else if let Some(text) = text.strip_suffix("/rc") {
+            (text, Strand::Minus)
+        } else {
+            let text = text.strip_suffix("(+)").unwrap_or(text);
+            (text.strip_suffix("(.)").unwrap_or(text), Strand::Plus)
+        };
+
+        let (contig, range) = text.rsplit_once(':')?;
+        let (start, end) = range.split_once('-')?;
+        let position = |p: &str| p.replace(',', "").parse::<usize>().ok();
+        let (start, end) = (position(start)?, position(end)?);
+        if contig.is_empty() || end < start {
+            return None;
+        }
+        let coords = match convention {
+            RegionConvention::Samtools if start > 0 => Coordinates::from_one_based(start, end),
+            RegionConvention::Samtools => return None,
+            RegionConvention::Bedtools => Coordinates::from_zero_based(start, end),
+        };
+        Some(Self {
+            contig: contig.to_string(),
+            coords,
+            strand,
+        })
+    }
+
+    /// A span and a strand of the query, on the contig, `None` when it's past the region.
+    pub fn lift(&self, coords: Coordinates, strand: Strand) -> Option<(Coordinates, Strand)> {
+        if coords.end() > self.coords.len() {
+            return None;
+        }
+        match self.strand {
+            Strand::Plus => Some((coords.shift(self.coords.start()), strand)),
+            Strand::Minus => {
+                let end = self.coords.end();
+                let lifted = Coordinates::from_zero_based(
+                    end.checked_sub(coords.end())?,
+                    end.checked_sub(coords.start())?,
+                );
+                let strand = match strand {
+                    Strand::Plus => Strand::Minus,
+                    Strand::Minus => Strand::Plus,
+                };
+                Some((lifted, strand))
+            }
+        }
+    }
+
+    /// A 0-based position of the query, on the contig, `None` when it's past the region.
+    pub fn lift_position(&self, position: usize) -> Option<usize> {
+        if position >= self.coords.len() {
+            return None;
+        }
+        match self.strand {
+            Strand::Plus => Some(self.coords.start() + position),
+            Strand::Minus => (self.coords.end() - 1).checked_sub(position),
+        }
+    }
+
+    /// The region of a span of the query, e.g. a piece of it, `None` when it's past the region.
+    pub fn sub_region(&self, coords: Coordinates) -> Option<Self> {
+        Some(Self {
+            contig: self.contig.clone(),
+            coords: self.lift(coords, Strand::Plus)?.0,
+            strand: self.strand,
+        })
+    }
+}
@@ -4,74 +4,159 @@
 
 use bincode::config::Configuration;
 use lazy_static::lazy_static;
-use std::collections::HashMap;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::OnceLock;
 
+use crate::annotation::AnnotationIndex;
+use crate::error::Error;
+use crate::matcher::{ExactAutomaton, IupacMatcher};
 use crate::place_desc::PlaceDB;
 
 lazy_static! {
 
     /// The initialized PLACE database,
-    /// as the instance of `place_desc:PlaceDB`.
-    pub static ref PLACE_DB: PlaceDB = {
-        init_place_db().expect("Failed to initialize PLACE database")
+    /// as the instance of `db::Database` over the embedded `place_desc:PlaceDB`.
+    pub static ref PLACE_DB: Database = {
+        // The committed place.db is checked against place.seq by the
+        // `embedded_db_matches_bundled_place_seq` test of tests/parser.rs
+        init_place_db()
+            .and_then(Database::new)
+            .expect("Failed to initialize PLACE database")
     };
+}
 
-    /// The IUPAC code map to regex pattern.
-    pub static ref IUPAC_MAP: HashMap<char, &'static str> = {
-        init_iupac_map()
-    };
+/// A PLACE database ready to be searched.
+///
+/// It owns the elements and the matchers compiled from them,
+/// so the matchers are built once per database and shared by every search.
+/// It dereferences to the `PlaceDB` it was built from.
+#[derive(Debug)]
+pub struct Database {
+    place_db: PlaceDB,
+    // Aho-Corasick automaton over the exact elements
+    exact_automaton: ExactAutomaton,
+    // bit-parallel matcher over the IUPAC elements
+    iupac_matcher: IupacMatcher,
+    // bit-parallel matcher over the exact elements,
+    // only built when a search allows mismatches or permissive ambiguous bases
+    exact_bitwise_matcher: OnceLock<IupacMatcher>,
+    // inverted index over the annotations, built on the first annotation query
+    annotation_index: OnceLock<AnnotationIndex>,
+}
+
+impl Database {
+    /// Compile the matchers of a `PlaceDB`.
+    pub fn new(place_db: PlaceDB) -> Result<Self, Error> {
+        let exact_automaton = ExactAutomaton::new(&place_db.seq_desc.exact)?;
+        let iupac_matcher = IupacMatcher::new(&place_db.seq_desc.iupac);
+        Ok(Self {
+            place_db,
+            exact_automaton,
+            iupac_matcher,
+            exact_bitwise_matcher: OnceLock::new(),
+            annotation_index: OnceLock::new(),
+        })
+    }
+
+    /// Load a database at runtime.
+    ///
+    /// A `.db` file is decoded as a serialized `PlaceDB` (the format of the embedded one),
+    /// any other file is parsed as a `place.seq` flat file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|ext| ext == "db") {
+            Self::from_db_file(path)
+        } else {
+            Self::from_place_seq_file(path)
+        }
+    }
+
+    /// Load a database from a `place.seq` flat file.
+    pub fn from_place_seq_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| with_path(e, path))?;
+        Self::new(PlaceDB::from_place_seq(&text)?)
+    }
+
+    /// Load a database from a serialized `PlaceDB` file.
+    pub fn from_db_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| with_path(e, path))?;
+        Self::new(decode_place_db(&bytes)?)
+    }
+
+    /// The elements of the database.
+    pub fn place_db(&self) -> &PlaceDB {
+        &self.place_db
+    }
+
+    /// The Aho-Corasick automaton over the exact elements.
+    pub fn exact_automaton(&self) -> &ExactAutomaton {
+        &self.exact_automaton
+    }
+
+    /// The bit-parallel matcher over the IUPAC elements.
+    pub fn iupac_matcher(&self) -> &IupacMatcher {
+        &self.iupac_matcher
+    }
+
+    /// The bit-parallel matcher over the exact elements, built on first use.
+    pub fn exact_bitwise_matcher(&self) -> &IupacMatcher {
+        self.exact_bitwise_matcher
+            .get_or_init(|| IupacMatcher::new(&self.place_db.seq_desc.exact))
+    }
+
+    /// The length of the longest element, 0 for an empty database.
+    pub fn max_element_len(&self) -> usize {
+        self.place_db
+            .seq_desc
+            .all
+            .iter()
+            .map(|desc| desc.sq.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The inverted index over `kw`, `os` and `de` of all elements, built on first use.
+    pub fn annotation_index(&self) -> &AnnotationIndex {
+        self.annotation_index
+            .get_or_init(|| AnnotationIndex::new(&self.place_db.seq_desc.all))
+    }
+}
+
+impl Deref for Database {
+    type Target = PlaceDB;
+
+    fn deref(&self) -> &PlaceDB {
+        &self.place_db
+    }
 }
 
 /// The function will only run in lazy_static context.
 ///
 /// The `init_place_db` function initializes the PLACE database
 /// from the file that serialized into the source while building the `placecare`.
-fn init_place_db() -> Result<PlaceDB, String> {
+fn init_place_db() -> Result<PlaceDB, Error> {
     let place_db: &[u8] = include_bytes!("./db_file/place.db");
+    decode_place_db(place_db)
+}
 
+/// Decode a `PlaceDB` serialized by the build script.
+fn decode_place_db(bytes: &[u8]) -> Result<PlaceDB, Error> {
     let read_config = bincode::config::standard().with_variable_int_encoding();
 
     let (bin_db, _) =
-        bincode::borrow_decode_from_slice::<PlaceDB, Configuration>(place_db, read_config)
-            .map_err(|e| format!("Failed to decode place_db: {}", e))?;
+        bincode::borrow_decode_from_slice::<PlaceDB, Configuration>(bytes, read_config)
+            .map_err(|e| Error::DbDecode(e.to_string()))?;
 
     Ok(bin_db)
 }
 
-/// The function will initialize the IUPAC map.
-fn init_iupac_map() -> HashMap<char, &'static str> {
-    let mut m = HashMap::new();
-    m.insert('A', "A");
-    m.insert('C', "C");
-    m.insert('G', "G");
-    m.insert('T', "T");
-    m.insert('R', "[AG]");
-    m.insert('Y', "[CT]");
-    m.insert('M', "[AC]");
-    m.insert('K', "[GT]");
-    m.insert('S', "[GC]");
-    m.insert('W', "[AT]");
-    m.insert('B', "[CGT]");
-    m.insert('D', "[AGT]");
-    m.insert('H', "[ACT]");
-    m.insert('V', "[ACG]");
-    m.insert('N', "[ACGT]");
-
-    // m.insert('A', "[AN]");
-    // m.insert('C', "[CN]");
-    // m.insert('G', "[GN]");
-    // m.insert('T', "[TN]");
-    // m.insert('R', "[AGN]");
-    // m.insert('Y', "[CTN]");
-    // m.insert('M', "[ACN]");
-    // m.insert('K', "[GTN]");
-    // m.insert('S', "[GCN]");
-    // m.insert('W', "[ATN]");
-    // m.insert('B', "[CGTN]");
-    // m.insert('D', "[AGTN]");
-    // m.insert('H', "[ACTN]");
-    // m.insert('V', "[ACGN]");
-    // m.insert('N', "[ACGTN]");
-
-    m
+/// Put the path of the file in an I/O error.
+fn with_path(e: std::io::Error, path: &Path) -> Error {
+    Error::Io(std::io::Error::new(
+        e.kind(),
+        format!("{}: {}", path.display(), e),
+    ))
 }
@@ -0,0 +1,307 @@
+//!
+//! Density of the hits in sliding windows along the queries, to find regulatory hotspots.
+//!
+//! The windows are `window` bases long and start every `step` bases,
+//! the last one is cut at the end of the query. A hit counts in every window it overlaps.
+//!
+//! The hits are counted in tracks, by `DensityGroup`: all together,
+//! one track per element, or one per PLACE keyword (`KW`) of the elements.
+//!
+//! The tracks are written as:
+//!
+//! - `Tsv`: a table of the windows, 1-based closed, with the hits and the hits per kb,
+//! - `BedGraph`: one `track` per group, 0-based half-open, the hits per kb
+//!   of each window up to the start of the next one, as the intervals mustn't overlap,
+//! - `Wig`: one `track` per group, a `fixedStep` block per query, the hits per kb
+//!   of each window at its start, spanning `step` bases.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+use crate::coords::Coordinates;
+use crate::db::Database;
+use crate::error::Error;
+use crate::io::{RecordDesc, SearchResult, SearchedDesc};
+use crate::place_search::{Search, SearchOptions};
+
+/// The name of the track of `DensityGroup::All`.
+pub const ALL_ELEMENTS: &str = "all";
+
+/// How the hits are grouped in tracks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DensityGroup {
+    /// One track of all the elements.
+    #[default]
+    All,
+    /// One track per element ID.
+    Elements,
+    /// One track per PLACE keyword, an element counts in each of its keywords.
+    Keywords,
+}
+
+/// The formats the tracks can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DensityFormat {
+    #[default]
+    Tsv,
+    BedGraph,
+    Wig,
+}
+
+/// The sliding windows and the grouping of the hits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DensityOptions {
+    pub window: usize,
+    pub step: usize,
+    pub group: DensityGroup,
+}
+
+impl Default for DensityOptions {
+    fn default() -> Self {
+        Self {
+            window: 100,
+            step: 50,
+            group: DensityGroup::default(),
+        }
+    }
+}
+
+impl DensityOptions {
+    fn check(&self) -> Result<(), Error> {
+        if self.window == 0 || self.step == 0 {
+            return Err(Error::InvalidOptions(
+                "the window and the step must be at least 1 base".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The windows along a query of `len` bases.
+    pub fn windows(&self, len: usize) -> Vec<Coordinates> {
+        if len == 0 || self.window == 0 || self.step == 0 {
+            return vec![];
+        }
+        (0..window_count(len, self.window, self.step))
+            .map(|i| {
+                let start = i * self.step;
+                Coordinates::from_zero_based(start, (start + self.window).min(len))
+            })
+            .collect()
+    }
+}
+
+/// The windows of a query, the last one reaching its end.
+fn window_count(len: usize, window: usize, step: usize) -> usize {
+    if len <= window {
+        1
+    } else {
+        (len - window).div_ceil(step) + 1
+    }
+}
+
+/// The hits of a group in one window of a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Window {
+    pub query_id: String,
+    pub coords: Coordinates,
+    pub hits: usize,
+}
+
+impl Window {
+    /// Hits per 1000 bases of the window.
+    pub fn per_kb(&self) -> f64 {
+        if self.coords.is_empty() {
+            return 0.0;
+        }
+        self.hits as f64 * 1000.0 / self.coords.len() as f64
+    }
+}
+
+/// The windows of every query for one group, by query then position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityTrack {
+    pub group: String,
+    pub windows: Vec<Window>,
+}
+
+/// Count the hits of each group in the windows of the queries.
+///
+/// `results` are the search results of `records`, in the same order.
+/// The groups without any hit have no track, the tracks are sorted by group.
+pub fn density(
+    db: &Database,
+    records: &[RecordDesc],
+    results: &[SearchResult],
+    options: &DensityOptions,
+) -> Result<Vec<DensityTrack>, Error> {
+    options.check()?;
+    let keywords: HashMap<&str, &[String]> = match options.group {
+        DensityGroup::Keywords => db
+            .place_db()
+            .seq_desc
+            .all
+            .iter()
+            .map(|desc| (desc.id.as_str(), desc.kw.as_slice()))
+            .collect(),
+        DensityGroup::All | DensityGroup::Elements => HashMap::new(),
+    };
+    let groups = |desc: &SearchedDesc<'_>| -> Vec<String> {
+        match options.group {
+            DensityGroup::All => vec![ALL_ELEMENTS.to_string()],
+            DensityGroup::Elements => vec![desc.e_id.to_string()],
+            DensityGroup::Keywords => keywords
+                .get(desc.e_id)
+                .map(|kw| kw.to_vec())
+                .unwrap_or_default(),
+        }
+    };
+
+    // Counts of each group in each window of each query
+    let mut counts: BTreeMap<String, Vec<Vec<usize>>> = BTreeMap::new();
+    let windows: Vec<Vec<Coordinates>> = records.iter().map(|r| options.windows(r.len())).collect();
+    for (query, result) in results.iter().enumerate().take(records.len()) {
+        let len = records[query].len();
+        for desc in &result.search_descs {
+            let Some(span) = overlapped(desc.q_coords, len, options) else {
+                continue;
+            };
+            for group in groups(desc) {
+                let track = counts
+                    .entry(group)
+                    .or_insert_with(|| windows.iter().map(|w| vec![0; w.len()]).collect());
+                for i in span.clone() {
+                    track[query][i] += 1;
+                }
+            }
+        }
+    }
+
+    Ok(counts
+        .into_iter()
+        .map(|(group, track)| DensityTrack {
+            group,
+            windows: records
+                .iter()
+                .zip(&windows)
+                .zip(track)
+                .flat_map(|((record, coords), hits)| {
+                    coords.iter().zip(hits).map(|(&coords, hits)| Window {
+                        query_id: record.id().to_string(),
+                        coords,
+                        hits,
+                    })
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+/// Search one query and count its hits in its windows.
+pub fn search_density(
+    db: &Database,
+    record: &RecordDesc,
+    search: &SearchOptions,
+    options: &DensityOptions,
+) -> Result<Vec<DensityTrack>, Error> {
+    let results = Search::search_elements_single_seq_with(db, record, search)?;
+    density(db, std::slice::from_ref(record), &results, options)
+}
+
+/// The indices of the windows a hit overlaps.
+fn overlapped(
+    hit: Coordinates,
+    len: usize,
+    options: &DensityOptions,
+) -> Option<std::ops::Range<usize>> {
+    if hit.is_empty() || hit.start() >= len {
+        return None;
+    }
+    let last = window_count(len, options.window, options.step) - 1;
+    // The first window ending after the start of the hit, the last one starting before its end
+    let first = (hit.start() + 1)
+        .saturating_sub(options.window)
+        .div_ceil(options.step);
+    let end = ((hit.end().min(len) - 1) / options.step).min(last);
+    (first <= end).then(|| first..end + 1)
+}
+
+/// Write the tracks in a format, see the module doc.
+pub fn write_tracks<W: Write>(
+    tracks: &[DensityTrack],
+    format: DensityFormat,
+    options: &DensityOptions,
+    mut writer: W,
+) -> std::io::Result<()> {
+    match format {
+        DensityFormat::Tsv => {
+            writeln!(writer, "Query ID\tGroup\tStart\tEnd\tHits\tHits per kb")?;
+            for track in tracks {
+                for w in &track.windows {
+                    let (start, end) = w.coords.one_based_closed();
+                    writeln!(
+                        writer,
+                        "{}\t{}\t{}\t{}\t{}\t{:.3}",
+                        w.query_id,
+                        track.group,
+                        start,
+                        end,
+                        w.hits,
+                        w.per_kb()
+                    )?;
+                }
+            }
+        }
+        DensityFormat::BedGraph => {
+            for track in tracks {
+                writeln!(
+                    writer,
+                    "track type=bedGraph name=\"{}\" description=\"{} hits per kb\"",
+                    track.group, track.group
+                )?;
+                for (i, w) in track.windows.iter().enumerate() {
+                    let (start, mut end) = w.coords.zero_based_half_open();
+                    // Up to the start of the next window of the query
+                    if let Some(next) = track.windows.get(i + 1)
+                        && next.query_id == w.query_id
+                    {
+                        end = end.min(next.coords.zero_based_half_open().0);
+                    }
+                    writeln!(
+                        writer,
+                        "{}\t{}\t{}\t{:.3}",
+                        w.query_id,
+                        start,
+                        end,
+                        w.per_kb()
+                    )?;
+                }
+            }
+        }
+        DensityFormat::Wig => {
+            let span = options.step.min(options.window);
+            for track in tracks {
+                writeln!(
+                    writer,
+                    "track type=wiggle_0 name=\"{}\" description=\"{} hits per kb\"",
+                    track.group, track.group
+                )?;
+                let mut query: Option<&str> = None;
+                for w in &track.windows {
+                    if query != Some(w.query_id.as_str()) {
+                        query = Some(w.query_id.as_str());
+                        writeln!(
+                            writer,
+                            "fixedStep chrom={} start={} step={} span={}",
+                            w.query_id,
+                            w.coords.start() + 1,
+                            options.step,
+                            span
+                        )?;
+                    }
+                    writeln!(writer, "{:.3}", w.per_kb())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,280 @@
+//!
+//! Enrichment of the elements in a foreground set of sequences against a background set.
+//!
+//! An element is counted once per sequence it hits, whatever the number of hits,
+//! and its share of foreground sequences is tested against the background one:
+//!
+//! - `Hypergeometric`: the foreground is drawn from the background,
+//!   e.g. the promoters of some genes against the promoters of the whole genome,
+//! - `Fisher`: the two sets are distinct, one-sided Fisher's exact test on
+//!   `[[fg with, fg without], [bg with, bg without]]`.
+//!
+//! Only the elements hitting at least one sequence of either set are tested,
+//! the p-values are corrected for them with Benjamini-Hochberg.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::io::SearchResult;
+
+/// The test of over-representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnrichmentTest {
+    /// The foreground sequences are part of the background ones.
+    Hypergeometric,
+    /// The foreground and the background sequences are distinct.
+    #[default]
+    Fisher,
+}
+
+/// The enrichment of one element.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElementEnrichment {
+    pub element_id: String,
+    pub element_accession: String,
+    /// Hits in the foreground sequences.
+    pub fg_hits: usize,
+    /// Foreground sequences with at least one hit.
+    pub fg_seqs: usize,
+    pub bg_hits: usize,
+    pub bg_seqs: usize,
+    /// Share of foreground sequences with a hit over the share of background ones,
+    /// infinite when no background sequence has one.
+    pub fold: f64,
+    /// One-sided p-value of over-representation.
+    pub p_value: f64,
+    /// Benjamini-Hochberg adjusted p-value.
+    pub q_value: f64,
+}
+
+/// The enrichment of all the elements, by increasing p-value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Enrichment {
+    /// Sequences of the foreground set.
+    pub fg_total: usize,
+    /// Sequences of the background set.
+    pub bg_total: usize,
+    pub elements: Vec<ElementEnrichment>,
+}
+
+/// Hits and sequences with a hit of an element in one set.
+#[derive(Default)]
+struct Occurrence<'a> {
+    accession: &'a str,
+    hits: usize,
+    seqs: usize,
+}
+
+/// Count the hits and the sequences with a hit of each element.
+fn count<'a>(results: &'a [SearchResult<'a>]) -> HashMap<&'a str, Occurrence<'a>> {
+    let mut counts: HashMap<&str, Occurrence> = HashMap::new();
+    for result in results {
+        let mut seen: HashSet<&str> = HashSet::new();
+        for desc in &result.search_descs {
+            let occurrence = counts.entry(desc.e_id).or_default();
+            occurrence.accession = desc.e_ac;
+            occurrence.hits += 1;
+            if seen.insert(desc.e_id) {
+                occurrence.seqs += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Test the elements of the foreground results against the background ones.
+pub fn enrich(
+    foreground: &[SearchResult],
+    background: &[SearchResult],
+    test: EnrichmentTest,
+) -> Enrichment {
+    let (fg_total, bg_total) = (foreground.len(), background.len());
+    let fg = count(foreground);
+    let bg = count(background);
+
+    // The population the foreground is drawn from,
+    // never smaller than the foreground, even if it isn't really drawn from the background
+    let population = match test {
+        EnrichmentTest::Hypergeometric => bg_total.max(fg_total),
+        EnrichmentTest::Fisher => fg_total + bg_total,
+    };
+    let log_factorials = LogFactorials::new(population);
+
+    let mut ids: Vec<&str> = fg.keys().chain(bg.keys()).copied().collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let empty = Occurrence::default();
+    let mut elements: Vec<ElementEnrichment> = ids
+        .into_iter()
+        .map(|id| {
+            let in_fg = fg.get(id).unwrap_or(&empty);
+            let in_bg = bg.get(id).unwrap_or(&empty);
+            let successes = match test {
+                EnrichmentTest::Hypergeometric => in_bg.seqs.max(in_fg.seqs),
+                EnrichmentTest::Fisher => in_fg.seqs + in_bg.seqs,
+            };
+            let p_value = log_factorials.upper_tail(population, successes, fg_total, in_fg.seqs);
+            ElementEnrichment {
+                element_id: id.to_string(),
+                element_accession: if in_fg.hits > 0 {
+                    in_fg.accession.to_string()
+                } else {
+                    in_bg.accession.to_string()
+                },
+                fg_hits: in_fg.hits,
+                fg_seqs: in_fg.seqs,
+                bg_hits: in_bg.hits,
+                bg_seqs: in_bg.seqs,
+                fold: fold(in_fg.seqs, fg_total, in_bg.seqs, bg_total),
+                p_value,
+                q_value: p_value,
+            }
+        })
+        .collect();
+
+    let p_values: Vec<f64> = elements.iter().map(|e| e.p_value).collect();
+    for (element, q_value) in elements.iter_mut().zip(benjamini_hochberg(&p_values)) {
+        element.q_value = q_value;
+    }
+    elements.sort_by(|a, b| {
+        a.p_value
+            .total_cmp(&b.p_value)
+            .then_with(|| b.fold.total_cmp(&a.fold))
+            .then_with(|| a.element_id.cmp(&b.element_id))
+    });
+
+    Enrichment {
+        fg_total,
+        bg_total,
+        elements,
+    }
+}
+
+/// Share of foreground sequences with a hit over the share of background ones.
+fn fold(fg_seqs: usize, fg_total: usize, bg_seqs: usize, bg_total: usize) -> f64 {
+    if fg_total == 0 {
+        return 0.0;
+    }
+    let fg_share = fg_seqs as f64 / fg_total as f64;
+    if bg_seqs == 0 || bg_total == 0 {
+        return if fg_seqs == 0 { 0.0 } else { f64::INFINITY };
+    }
+    fg_share / (bg_seqs as f64 / bg_total as f64)
+}
+
+/// The q-values of p-values, Benjamini-Hochberg step-up.
+pub(crate) fn benjamini_hochberg(p_values: &[f64]) -> Vec<f64> {
+    let tests = p_values.len();
+    let mut order: Vec<usize> = (0..tests).collect();
+    order.sort_by(|&a, &b| p_values[a].total_cmp(&p_values[b]));
+
+    // From the largest p-value down, keeping the running minimum
+    let mut q_values = vec![1.0; tests];
+    let mut min_q: f64 = 1.0;
+    for (rank, &index) in order.iter().enumerate().rev() {
+        let q = p_values[index] * tests as f64 / (rank + 1) as f64;
+        min_q = min_q.min(q);
+        q_values[index] = min_q;
+    }
+    q_values
+}
+
+/// `ln(n!)` for `n` up to a bound, to compute hypergeometric probabilities exactly.
+pub(crate) struct LogFactorials(Vec<f64>);
+
+impl LogFactorials {
+    pub(crate) fn new(max: usize) -> Self {
+        let mut table = Vec::with_capacity(max + 1);
+        table.push(0.0);
+        for n in 1..=max {
+            table.push(table[n - 1] + (n as f64).ln());
+        }
+        Self(table)
+    }
+
+    fn ln_choose(&self, n: usize, k: usize) -> f64 {
+        self.0[n] - self.0[k] - self.0[n - k]
+    }
+
+    /// `P(X >= k)` when drawing `draws` of `population` items, `successes` of them successes.
+    pub(crate) fn upper_tail(
+        &self,
+        population: usize,
+        successes: usize,
+        draws: usize,
+        k: usize,
+    ) -> f64 {
+        let successes = successes.min(population);
+        let draws = draws.min(population);
+        let low = k.max((draws + successes).saturating_sub(population));
+        let high = draws.min(successes);
+        if low > high {
+            return if k == 0 { 1.0 } else { 0.0 };
+        }
+
+        let ln_total = self.ln_choose(population, draws);
+        let terms: Vec<f64> = (low..=high)
+            .map(|x| {
+                self.ln_choose(successes, x) + self.ln_choose(population - successes, draws - x)
+                    - ln_total
+            })
+            .collect();
+        log_sum_exp(&terms).min(1.0)
+    }
+
+    /// `P(X >= k)` for `X ~ Binomial(n, p)`, `n` being at most the bound of the table.
+    pub(crate) fn binomial_upper_tail(&self, n: usize, k: usize, p: f64) -> f64 {
+        if k == 0 {
+            return 1.0;
+        }
+        if k > n || p <= 0.0 {
+            return 0.0;
+        }
+        if p >= 1.0 {
+            return 1.0;
+        }
+        let terms: Vec<f64> = (k..=n)
+            .map(|x| self.ln_choose(n, x) + x as f64 * p.ln() + (n - x) as f64 * (1.0 - p).ln())
+            .collect();
+        log_sum_exp(&terms).min(1.0)
+    }
+}
+
+/// `exp` of the sum of exponentials of log terms,
+/// which can be far below f64's smallest positive value.
+fn log_sum_exp(terms: &[f64]) -> f64 {
+    let max = terms.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let sum: f64 = terms.iter().map(|t| (t - max).exp()).sum();
+    (max + sum.ln()).exp()
+}
+
+impl fmt::Display for Enrichment {
+    /// A TSV table, with a header line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Element ID\tElement Accession\tFG Hits\tFG Sequences\tFG Total\tBG Hits\tBG Sequences\tBG Total\tFold\tP-value\tQ-value"
+        )?;
+        for e in &self.elements {
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.3}\t{:.3e}\t{:.3e}",
+                e.element_id,
+                e.element_accession,
+                e.fg_hits,
+                e.fg_seqs,
+                self.fg_total,
+                e.bg_hits,
+                e.bg_seqs,
+                self.bg_total,
+                e.fold,
+                e.p_value,
+                e.q_value
+            )?;
+        }
+        Ok(())
+    }
+}
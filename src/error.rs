@@ -0,0 +1,78 @@
+//!
+//! The error type of the library.
+//!
+//! Every fallible public function returns `placecare::Error`,
+//! the library doesn't panic on bad input nor write anything to stdout/stderr.
+
+use std::fmt;
+
+use crate::parser::ParseError;
+
+/// The error returned by the fallible functions of the library.
+#[derive(Debug)]
+pub enum Error {
+    /// A file or a stream couldn't be read or written.
+    Io(std::io::Error),
+    /// The FASTA input is malformed.
+    Fasta(String),
+    /// A sequence of the input can't be searched.
+    InvalidSequence { id: String, message: String },
+    /// A `place.seq` file couldn't be parsed.
+    Parse(ParseError),
+    /// A serialized database couldn't be decoded.
+    DbDecode(String),
+    /// The matchers of a database couldn't be built.
+    DbBuild(String),
+    /// An element given by ID or accession isn't in the database.
+    UnknownElement(String),
+    /// An annotation query or a filter is malformed.
+    InvalidQuery(String),
+    /// A GFF3/GTF annotation is malformed or doesn't fit the genome.
+    Annotation(String),
+    /// The options of a computation are out of their range.
+    InvalidOptions(String),
+}
+
+/// A `Result` with `placecare::Error` as the error.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Fasta(message) => write!(f, "Invalid FASTA: {}", message),
+            Error::InvalidSequence { id, message } => {
+                write!(f, "Invalid sequence {}: {}", id, message)
+            }
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::DbDecode(message) => write!(f, "Failed to decode the database: {}", message),
+            Error::DbBuild(message) => write!(f, "Failed to build the database: {}", message),
+            Error::UnknownElement(name) => write!(f, "Unknown element: {}", name),
+            Error::InvalidQuery(message) => write!(f, "Invalid query: {}", message),
+            Error::Annotation(message) => write!(f, "Invalid annotation: {}", message),
+            Error::InvalidOptions(message) => write!(f, "Invalid options: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
@@ -0,0 +1,152 @@
+//!
+//! Filters restricting the elements a search reports.
+//!
+//! A filter is part of the `SearchOptions`, the hits of the elements it rejects
+//! are dropped before they're turned into results.
+//! It's applied to the elements once per search run, and a matcher is skipped
+//! when none of its elements pass, but the matchers themselves aren't rebuilt:
+//! the filter restricts what's reported, not what a scan looks for.
+//! All the conditions which are set must hold for an element to be kept.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::annotation::contains_tokens;
+use crate::db::Database;
+use crate::error::Error;
+use crate::place_desc::SeqDesc;
+
+/// Conditions on the elements of a search, an empty filter keeps every element.
+#[derive(Debug, Clone, Default)]
+pub struct ElementFilter {
+    /// Keep only the elements with these IDs.
+    pub ids: HashSet<String>,
+    /// Keep only the elements with these accessions.
+    ///
+    /// When both `ids` and `acs` are given, an element listed in either is kept.
+    pub acs: HashSet<String>,
+    /// Keep only the elements with a keyword matching all tokens of the text, e.g. `auxin`.
+    pub keyword: Option<String>,
+    /// Keep only the elements from an organism matching all tokens of the text.
+    pub organism: Option<String>,
+    /// Minimum element length.
+    pub min_len: Option<usize>,
+    /// Maximum element length.
+    pub max_len: Option<usize>,
+    /// Keep only the elements whose ID matches the regex.
+    pub id_regex: Option<Regex>,
+    /// Drop the elements with these IDs or accessions.
+    pub exclude: HashSet<String>,
+}
+
+impl ElementFilter {
+    /// Whether the filter has no condition.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+            && self.acs.is_empty()
+            && self.keyword.is_none()
+            && self.organism.is_none()
+            && self.min_len.is_none()
+            && self.max_len.is_none()
+            && self.id_regex.is_none()
+            && self.exclude.is_empty()
+    }
+
+    /// Whether the element passes the filter.
+    pub fn accepts(&self, desc: &SeqDesc) -> bool {
+        let listed = (self.ids.is_empty() && self.acs.is_empty())
+            || self.ids.contains(&desc.id)
+            || self.acs.contains(&desc.ac);
+        let len = desc.sq.len();
+
+        listed
+            && !self.exclude.contains(&desc.id)
+            && !self.exclude.contains(&desc.ac)
+            && self.min_len.is_none_or(|min| len >= min)
+            && self.max_len.is_none_or(|max| len <= max)
+            && self
+                .keyword
+                .as_ref()
+                .is_none_or(|text| desc.kw.iter().any(|kw| contains_tokens(kw, text)))
+            && self
+                .organism
+                .as_ref()
+                .is_none_or(|os| contains_tokens(&desc.os, os))
+            && self
+                .id_regex
+                .as_ref()
+                .is_none_or(|re| re.is_match(&desc.id))
+    }
+
+    /// Keep only the elements listed in a file, by ID or accession.
+    pub fn include_file<P: AsRef<Path>>(mut self, path: P) -> Result<Self, Error> {
+        let names = read_names(path)?;
+        self.ids.extend(names.iter().cloned());
+        self.acs.extend(names);
+        Ok(self)
+    }
+
+    /// Drop the elements listed in a file, by ID or accession.
+    pub fn exclude_file<P: AsRef<Path>>(mut self, path: P) -> Result<Self, Error> {
+        self.exclude.extend(read_names(path)?);
+        Ok(self)
+    }
+
+    /// Check that the elements to keep are in the database, by ID or accession.
+    ///
+    /// The excluded ones may be missing, e.g. from a list shared between databases,
+    /// see `unknown_exclusions`.
+    pub fn check_names(&self, db: &Database) -> Result<(), Error> {
+        let unknown = self
+            .ids
+            .iter()
+            .chain(&self.acs)
+            .find(|name| !is_known(db, name));
+        match unknown {
+            Some(name) => Err(Error::UnknownElement(name.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// The excluded names which aren't in the database, sorted.
+    pub fn unknown_exclusions(&self, db: &Database) -> Vec<&str> {
+        let mut unknown: Vec<&str> = self
+            .exclude
+            .iter()
+            .filter(|name| !is_known(db, name))
+            .map(String::as_str)
+            .collect();
+        unknown.sort_unstable();
+        unknown
+    }
+
+    /// Which of the elements pass the filter, `None` when the filter is empty.
+    pub(crate) fn mask(&self, descs: &[SeqDesc]) -> Option<Vec<bool>> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(descs.iter().map(|desc| self.accepts(desc)).collect())
+    }
+}
+
+/// Whether a name is the ID or the accession of an element of the database.
+fn is_known(db: &Database, name: &str) -> bool {
+    db.seq_index.id_index.contains_key(name) || db.seq_index.ac_index.contains_key(name)
+}
+
+/// Read the names of a list file, one per line.
+///
+/// Blank lines and lines starting with `#` are skipped,
+/// only the first column of a line is used.
+pub fn read_names<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<String>> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|name| name.to_string())
+        .collect())
+}
@@ -3,6 +3,13 @@
 
 use bio::io::fasta::Records;
 use std::fmt::Debug;
+use std::io::BufRead;
+
+use serde::{Deserialize, Serialize};
+
+use crate::coords::{CoordinateSystem, Coordinates, RegionConvention, SourceRegion, Strand};
+use crate::db::Database;
+use crate::error::Error;
 
 /// The structure is used to describe the input query sequence.
 #[derive(Debug, Clone)]
@@ -10,15 +17,105 @@ pub struct RecordDesc {
     id: String,  // input query sequence id
     seq: String, // input query sequence
     len: usize,  // input query sequence length
+    // 0-based index of the TSS or ATG the positions are reported from
+    anchor: Option<isize>,
+    // description of the FASTA header
+    desc: Option<String>,
+    // region of the genome the sequence was cut from
+    source: Option<SourceRegion>,
 }
 
 impl RecordDesc {
     /// create a new RecordDesc.
     pub fn new(id: &str, seq: &str) -> Self {
+        Self::from_string_seq(id, seq.to_string())
+    }
+
+    /// create a new RecordDesc from the bytes of a sequence.
+    ///
+    /// Each byte of invalid UTF-8 becomes an `N`, so the positions stay byte offsets.
+    pub fn from_bytes(id: &str, seq: &[u8]) -> Self {
+        let mut text = String::with_capacity(seq.len());
+        for chunk in seq.utf8_chunks() {
+            text.push_str(chunk.valid());
+            text.extend(chunk.invalid().iter().map(|_| 'N'));
+        }
+        Self::from_string_seq(id, text)
+    }
+
+    /// Only ASCII letters are upper-cased, so the positions are byte offsets
+    /// and every base takes one byte.
+    fn from_string_seq(id: &str, mut seq: String) -> Self {
+        seq.make_ascii_uppercase();
         Self {
             id: id.to_owned(),
-            seq: seq.to_string().to_uppercase(),
             len: seq.len(),
+            seq,
+            anchor: None,
+            desc: None,
+            source: None,
+        }
+    }
+
+    /// Set the TSS or ATG of the record, as a 0-based index in the sequence.
+    ///
+    /// It may be outside the sequence, e.g. `len()` for a region right upstream of a TSS.
+    pub fn with_anchor(mut self, anchor: isize) -> Self {
+        self.anchor = Some(anchor);
+        self
+    }
+
+    /// The 0-based index of the TSS or ATG, if the record has one.
+    pub fn anchor(&self) -> Option<isize> {
+        self.anchor
+    }
+
+    /// The description of the FASTA header, the text after the ID.
+    pub fn desc(&self) -> Option<&str> {
+        self.desc.as_deref()
+    }
+
+    /// Set the region of the genome the sequence was cut from,
+    /// a region which isn't as long as the sequence is an error.
+    pub fn with_source(mut self, source: SourceRegion) -> Result<Self, Error> {
+        if source.coords.len() != self.len {
+            let (start, end) = source.coords.one_based_closed();
+            return Err(Error::InvalidSequence {
+                id: self.id.clone(),
+                message: format!(
+                    "region {}:{}-{} is {} bases long, the sequence {}",
+                    source.contig,
+                    start,
+                    end,
+                    source.coords.len(),
+                    self.len
+                ),
+            });
+        }
+        self.source = Some(source);
+        Ok(self)
+    }
+
+    /// The region of the genome the sequence was cut from, if known.
+    pub fn source(&self) -> Option<&SourceRegion> {
+        self.source.as_ref()
+    }
+
+    /// Set the source region from the header, the ID first then the words of the description.
+    ///
+    /// The record is left as it is when there's no region in the header,
+    /// a region which isn't as long as the sequence is an error.
+    pub fn with_header_region(self, convention: RegionConvention) -> Result<Self, Error> {
+        let words = self
+            .desc()
+            .into_iter()
+            .flat_map(|desc| desc.split_whitespace());
+        let region = std::iter::once(self.id())
+            .chain(words)
+            .find_map(|word| SourceRegion::parse(word, convention));
+        match region {
+            Some(region) => self.with_source(region),
+            None => Ok(self),
         }
     }
 
@@ -30,29 +127,46 @@ impl RecordDesc {
         &self.seq
     }
 
+    /// The sequence as bytes, one per base, which is what the matchers scan.
+    pub fn seq_bytes(&self) -> &[u8] {
+        self.seq.as_bytes()
+    }
+
     pub fn len(&self) -> usize {
         self.len
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     /// create new RecordDescs from fasta records of [bio] crate.
-    pub fn from_records<B>(records: Records<B>) -> Vec<Self>
+    ///
+    /// A `tss=` or `atg=` word in the description, the 1-based position
+    /// of the anchor in the sequence, sets the anchor of the record.
+    pub fn from_records<B>(records: Records<B>) -> Result<Vec<Self>, Error>
     where
         B: std::io::BufRead + Debug,
     {
         let mut res = vec![];
         for record in records {
-            let record = record.expect("Error<bio>: Failed to read record");
-            let id = record.id();
-            let seq = record.seq().to_owned();
-            let seq = std::str::from_utf8(&seq).expect("Error<bio>: Invalid UTF-8");
-
-            res.push(Self::new(id, &seq));
+            let record = record.map_err(|e| Error::Fasta(e.to_string()))?;
+            let seq = std::str::from_utf8(record.seq()).map_err(|e| Error::InvalidSequence {
+                id: record.id().to_string(),
+                message: e.to_string(),
+            })?;
+            let mut desc = Self::new(record.id(), seq);
+            if let Some(anchor) = record.desc().and_then(header_anchor) {
+                desc = desc.with_anchor(anchor);
+            }
+            desc.desc = record.desc().map(|d| d.to_string());
+            res.push(desc);
         }
-        res
+        Ok(res)
     }
 
     /// create new RecordDescs from a Reader.
-    pub fn from_reader<R>(reader: R) -> Vec<Self>
+    pub fn from_reader<R>(reader: R) -> Result<Vec<Self>, Error>
     where
         R: std::io::Read + Debug,
     {
@@ -61,17 +175,17 @@ impl RecordDesc {
     }
 
     /// create new RecordDescs from a file.
-    pub fn from_file<P>(reader: P) -> Vec<Self>
+    pub fn from_file<P>(reader: P) -> Result<Vec<Self>, Error>
     where
         P: AsRef<std::path::Path> + std::fmt::Display + Debug,
     {
-        let reader =
-            bio::io::fasta::Reader::from_file(reader).expect("Error<bio>: Failed to read file");
-        Self::from_records(reader.records())
+        let file = std::fs::File::open(&reader)
+            .map_err(|e| Error::Io(std::io::Error::new(e.kind(), format!("{}: {}", reader, e))))?;
+        Self::from_reader(file)
     }
 
     /// create new RecordDescs from a string.
-    pub fn from_string<P>(string: P) -> Vec<Self>
+    pub fn from_string<P>(string: P) -> Result<Vec<Self>, Error>
     where
         P: AsRef<str> + std::fmt::Display,
     {
@@ -80,6 +194,162 @@ impl RecordDesc {
     }
 }
 
+/// The 0-based anchor given by a `tss=<1-based position>` or `atg=...` word of a description.
+fn header_anchor(desc: &str) -> Option<isize> {
+    desc.split_whitespace().find_map(|word| {
+        let (key, value) = word.split_once('=')?;
+        if !(key.eq_ignore_ascii_case("tss") || key.eq_ignore_ascii_case("atg")) {
+            return None;
+        }
+        value.parse::<isize>().ok().map(|position| position - 1)
+    })
+}
+
+/// A piece of a FASTA record, read by `FastaChunks`.
+#[derive(Debug, Clone)]
+pub struct SeqChunk {
+    /// The record id and the bases of the chunk.
+    pub record: RecordDesc,
+    /// The 0-based position of the first base of the chunk in the record.
+    pub offset: usize,
+    /// The bases owned by the chunk, the ones after it are shared with the next chunk.
+    pub core_len: usize,
+}
+
+/// A streaming FASTA reader which cuts the records into overlapping chunks.
+///
+/// Every chunk holds `chunk_len` bases of its own plus the `overlap` first bases of the next one,
+/// so a pattern not longer than `overlap + 1` starting in a chunk is found whole in it.
+/// The memory used doesn't depend on the length of the records or of the lines.
+pub struct FastaChunks<R> {
+    reader: R,
+    chunk_len: usize,
+    overlap: usize,
+    // id of the record being read, `None` between records
+    id: Option<String>,
+    // bases read from the record and not yet given away
+    buf: Vec<u8>,
+    // position of `buf[0]` in the record
+    offset: usize,
+    line_start: bool,
+}
+
+impl<R: BufRead> FastaChunks<R> {
+    pub fn new(reader: R, chunk_len: usize, overlap: usize) -> Self {
+        Self {
+            reader,
+            chunk_len: chunk_len.max(1),
+            overlap,
+            id: None,
+            buf: Vec::with_capacity(chunk_len + overlap),
+            offset: 0,
+            line_start: true,
+        }
+    }
+
+    /// Skip to the next header line and read the id of its record.
+    fn read_header(&mut self) -> std::io::Result<Option<String>> {
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                return Ok(None);
+            }
+            if self.line_start && available[0] == b'>' {
+                let mut header = vec![];
+                self.reader.read_until(b'\n', &mut header)?;
+                let header = String::from_utf8_lossy(&header[1..]);
+                let id = header.split_whitespace().next().unwrap_or("");
+                return Ok(Some(id.to_string()));
+            }
+            let used = match available.iter().position(|&b| b == b'\n') {
+                Some(p) => {
+                    self.line_start = true;
+                    p + 1
+                }
+                None => {
+                    self.line_start = false;
+                    available.len()
+                }
+            };
+            self.reader.consume(used);
+        }
+    }
+
+    /// Read bases until a full chunk is buffered, `true` when the record ended first.
+    fn fill(&mut self) -> std::io::Result<bool> {
+        let want = self.chunk_len + self.overlap;
+        while self.buf.len() < want {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() || (self.line_start && available[0] == b'>') {
+                return Ok(true);
+            }
+            let mut used = 0;
+            for &b in available {
+                used += 1;
+                if b == b'\n' {
+                    self.line_start = true;
+                    break;
+                }
+                self.line_start = false;
+                if !b.is_ascii_whitespace() {
+                    self.buf.push(b);
+                    if self.buf.len() == want {
+                        break;
+                    }
+                }
+            }
+            self.reader.consume(used);
+        }
+        Ok(false)
+    }
+
+    fn chunk(&self, id: &str, len: usize, core_len: usize) -> SeqChunk {
+        SeqChunk {
+            record: RecordDesc::from_bytes(id, &self.buf[..len]),
+            offset: self.offset,
+            core_len,
+        }
+    }
+
+    fn read_chunk(&mut self) -> std::io::Result<Option<SeqChunk>> {
+        loop {
+            let id = match &self.id {
+                Some(id) => id.clone(),
+                None => match self.read_header()? {
+                    Some(id) => {
+                        self.id = Some(id);
+                        self.buf.clear();
+                        self.offset = 0;
+                        continue;
+                    }
+                    None => return Ok(None),
+                },
+            };
+
+            if !self.fill()? {
+                let chunk = self.chunk(&id, self.buf.len(), self.chunk_len);
+                self.buf.drain(..self.chunk_len);
+                self.offset += self.chunk_len;
+                return Ok(Some(chunk));
+            }
+
+            // The record ended, its last bases make the last chunk
+            self.id = None;
+            if !self.buf.is_empty() {
+                return Ok(Some(self.chunk(&id, self.buf.len(), self.buf.len())));
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for FastaChunks<R> {
+    type Item = std::io::Result<SeqChunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_chunk().transpose()
+    }
+}
+
 /// The structure is used to describe the search result.
 #[derive(Debug, Clone)]
 pub struct SearchResult<'a> {
@@ -98,31 +368,57 @@ impl<'a> SearchResult<'a> {
     }
 
     pub fn sort_self(&mut self) {
-        self.search_descs.sort_by(|a, b| a.q_start.cmp(&b.q_start));
+        self.search_descs.sort_by_key(|a| a.q_coords.start());
+    }
+
+    /// The hits lifted to the genome the query was cut from, see `SearchedDesc::lift`.
+    pub fn lift(mut self) -> Self {
+        self.search_descs = self
+            .search_descs
+            .into_iter()
+            .map(|desc| desc.lift())
+            .collect();
+        self.sort_self();
+        self
+    }
+
+    /// Copy the result into an owned, serializable one, with positions in `system`.
+    pub fn to_owned_result(&self, system: CoordinateSystem) -> OwnedSearchResult {
+        OwnedSearchResult {
+            id: self.id.clone(),
+            count: self.count,
+            hits: self
+                .search_descs
+                .iter()
+                .map(|desc| desc.to_owned_desc(system))
+                .collect(),
+        }
     }
 }
 
-// TODO： usize -> String -> str
 /// The structure is used to describe the searched element in the database.
 #[derive(Clone, Debug)]
 pub struct SearchedDesc<'a> {
-    pub q_id: &'a str,   // input query sequence id
-    pub q_start: usize,  // start position of the query
-    pub q_end: usize,    // end position of the query
-    pub q_dir: usize,    // direction of the sequence, 0 for - and 1 for +
-    pub e_id: &'a str,   // element id
-    pub e_len: usize,    // element length
-    pub e_sq: &'a str,   // element sequence
-    pub e_ac: &'a str,   // element accession number
-    pub e_desc: &'a str, // element description
+    pub q_id: &'a str,                      // input query sequence id
+    pub q_coords: Coordinates,              // span of the hit on the query
+    pub q_strand: Strand,                   // strand of the query the element is on
+    pub e_id: &'a str,                      // element id
+    pub e_len: usize,                       // element length
+    pub e_sq: &'a str,                      // element sequence
+    pub e_ac: &'a str,                      // element accession number
+    pub e_desc: &'a str,                    // element description
+    pub mismatches: usize,                  // number of mismatched positions
+    pub mismatch_pos: Vec<usize>,           // mismatched positions on the query (0-based)
+    pub q_relative: Option<(isize, isize)>, // first and last base relative to the TSS or ATG
+    pub q_source: Option<&'a SourceRegion>, // region of the genome the query was cut from
 }
 
 impl<'a> SearchedDesc<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         q_id: &'a str,
-        q_start: usize,
-        q_end: usize,
-        q_dir: usize,
+        q_coords: Coordinates,
+        q_strand: Strand,
         e_id: &'a str,
         e_len: usize,
         e_sq: &'a str,
@@ -131,34 +427,232 @@ impl<'a> SearchedDesc<'a> {
     ) -> Self {
         Self {
             q_id,
-            q_start,
-            q_end,
-            q_dir,
+            q_coords,
+            q_strand,
             e_id,
             e_len,
             e_sq,
             e_ac,
             e_desc,
+            mismatches: 0,
+            mismatch_pos: vec![],
+            q_relative: None,
+            q_source: None,
         }
     }
-}
 
-impl<'a> std::fmt::Display for SearchedDesc<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t\n",
+    /// Record the anchor of the query, to report the hit relative to it.
+    pub fn with_anchor(mut self, anchor: Option<isize>) -> Self {
+        self.q_relative = anchor.map(|anchor| self.q_coords.relative_to(anchor));
+        self
+    }
+
+    /// Record the region of the genome the query was cut from, to lift the hit to it.
+    pub fn with_source(mut self, source: Option<&'a SourceRegion>) -> Self {
+        self.q_source = source;
+        self
+    }
+
+    /// The first and last base of the hit relative to the anchor of the query, e.g. `(-215, -210)`.
+    ///
+    /// They're relative to the query, lifting the hit doesn't change them.
+    pub fn relative(&self) -> Option<(isize, isize)> {
+        self.q_relative
+    }
+
+    /// The same hit on the genome the query was cut from, named after the contig,
+    /// or the hit as it is without a source region or when it's past the region.
+    pub fn lift(mut self) -> Self {
+        let Some(source) = self.q_source else {
+            return self;
+        };
+        let lifted = source.lift(self.q_coords, self.q_strand);
+        let positions: Option<Vec<usize>> = self
+            .mismatch_pos
+            .iter()
+            .map(|&p| source.lift_position(p))
+            .collect();
+        if let (Some((coords, strand)), Some(mut positions)) = (lifted, positions) {
+            positions.sort_unstable();
+            self.q_id = &source.contig;
+            self.q_coords = coords;
+            self.q_strand = strand;
+            self.mismatch_pos = positions;
+            self.q_source = None;
+        }
+        self
+    }
+
+    /// Record the mismatched positions (0-based) of a hit found with mismatches.
+    pub fn with_mismatches(mut self, positions: Vec<usize>) -> Self {
+        self.mismatches = positions.len();
+        self.mismatch_pos = positions;
+        self
+    }
+
+    /// The same hit moved `offset` bases forward on the query.
+    pub fn shift(mut self, offset: usize) -> Self {
+        self.q_coords = self.q_coords.shift(offset);
+        self.mismatch_pos.iter_mut().for_each(|p| *p += offset);
+        self
+    }
+
+    /// Copy the hit into an owned, serializable one, with positions in `system`.
+    pub fn to_owned_desc(&self, system: CoordinateSystem) -> OwnedSearchedDesc {
+        let (start, end) = self.q_coords.in_system(system);
+        let relative = self.relative();
+        OwnedSearchedDesc {
+            query_id: self.q_id.to_string(),
+            start,
+            end,
+            strand: self.q_strand,
+            coordinates: system,
+            element_id: self.e_id.to_string(),
+            element_length: self.e_len,
+            element_sequence: self.e_sq.to_string(),
+            element_accession: self.e_ac.to_string(),
+            element_description: self.e_desc.to_string(),
+            mismatches: self.mismatches,
+            mismatch_positions: self
+                .mismatch_pos
+                .iter()
+                .map(|&p| system.position(p))
+                .collect(),
+            relative_start: relative.map(|(start, _)| start),
+            relative_end: relative.map(|(_, end)| end),
+            keywords: None,
+            organism: None,
+        }
+    }
+
+    /// The header line of the rows written by `Display` and `row`.
+    pub fn header() -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            "Query ID",
+            "Query Start",
+            "Query End",
+            "Query Direction",
+            "Element ID",
+            "Element Length",
+            "Element Sequence",
+            "Element Accession",
+            "Element Description",
+            "Mismatches",
+            "Relative Start",
+            "Relative End"
+        )
+    }
+
+    /// The tab-separated row of the hit, with positions in `system`.
+    ///
+    /// The relative positions are `.` when the query has no anchor.
+    pub fn row(&self, system: CoordinateSystem) -> String {
+        let (start, end) = self.q_coords.in_system(system);
+        let (rel_start, rel_end) = match self.relative() {
+            Some((start, end)) => (start.to_string(), end.to_string()),
+            None => (".".to_string(), ".".to_string()),
+        };
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t\n",
             self.q_id,
-            self.q_start,
-            self.q_end,
-            self.q_dir,
+            start,
+            end,
+            self.q_strand,
             self.e_id,
             self.e_len,
             self.e_sq,
             self.e_ac,
-            self.e_desc
+            self.e_desc,
+            self.mismatches_text(system),
+            rel_start,
+            rel_end
         )
     }
+
+    /// The mismatches as text, e.g. `0` or `2(13,16)`.
+    fn mismatches_text(&self, system: CoordinateSystem) -> String {
+        if self.mismatches == 0 {
+            return "0".to_string();
+        }
+        let positions: Vec<String> = self
+            .mismatch_pos
+            .iter()
+            .map(|&p| system.position(p).to_string())
+            .collect();
+        format!("{}({})", self.mismatches, positions.join(","))
+    }
+}
+
+impl<'a> std::fmt::Display for SearchedDesc<'a> {
+    /// The row of the hit, 1-based closed.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.row(CoordinateSystem::OneBasedClosed))
+    }
+}
+
+/// An owned `SearchResult`, which can be kept after the database and the query are gone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnedSearchResult {
+    pub id: String,
+    pub count: usize,
+    pub hits: Vec<OwnedSearchedDesc>,
+}
+
+impl OwnedSearchResult {
+    /// Add the keywords and the organism of the elements, see `OwnedSearchedDesc::with_metadata`.
+    pub fn with_metadata(mut self, db: &Database) -> Self {
+        self.hits = self
+            .hits
+            .into_iter()
+            .map(|hit| hit.with_metadata(db))
+            .collect();
+        self
+    }
+}
+
+/// An owned `SearchedDesc`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnedSearchedDesc {
+    pub query_id: String,
+    pub start: usize,
+    pub end: usize,
+    pub strand: Strand,
+    /// The system of `start`, `end` and `mismatch_positions`.
+    pub coordinates: CoordinateSystem,
+    pub element_id: String,
+    pub element_length: usize,
+    pub element_sequence: String,
+    pub element_accession: String,
+    pub element_description: String,
+    pub mismatches: usize,
+    pub mismatch_positions: Vec<usize>,
+    /// First base relative to the TSS or ATG of the query, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_start: Option<isize>,
+    /// Last base relative to the TSS or ATG of the query, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_end: Option<isize>,
+    /// Keywords of the element, only when asked for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<String>>,
+    /// Organism source of the element, only when asked for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organism: Option<String>,
+}
+
+impl OwnedSearchedDesc {
+    /// Add the keywords and the organism of the element, looked up by ID in `db`.
+    ///
+    /// The hit is left as it is when the element isn't in `db`.
+    pub fn with_metadata(mut self, db: &Database) -> Self {
+        if let Some(&index) = db.seq_index.id_index.get(&self.element_id) {
+            let desc = &db.seq_desc.all[index];
+            self.keywords = Some(desc.kw.clone());
+            self.organism = Some(desc.os.clone());
+        }
+        self
+    }
 }
 
 /// A wrapper type around `Vec<SearchedDesc>` to implement Display.
@@ -170,26 +664,20 @@ impl<'a> From<Vec<SearchedDesc<'a>>> for SearchedDescList<'a> {
     }
 }
 
-impl<'a> std::fmt::Display for SearchedDescList<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut res = String::new();
-        let header = format!(
-            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
-            "Query ID",
-            "Query Start",
-            "Query End",
-            "Query Direction",
-            "Element ID",
-            "Element Length",
-            "Element Sequence",
-            "Element Accession",
-            "Element Description"
-        );
-        res.push_str(&header);
-        let mut rows = String::new();
+impl<'a> SearchedDescList<'a> {
+    /// The header and the rows of the hits, with positions in `system`.
+    pub fn table(&self, system: CoordinateSystem) -> String {
+        let mut table = SearchedDesc::header();
         for desc in &self.0 {
-            rows.push_str(&desc.to_string());
+            table.push_str(&desc.row(system));
         }
-        write!(f, "{}{}", header, rows)
+        table
+    }
+}
+
+impl<'a> std::fmt::Display for SearchedDescList<'a> {
+    /// The table of the hits, 1-based closed.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.table(CoordinateSystem::OneBasedClosed))
     }
 }
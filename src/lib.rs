@@ -12,30 +12,117 @@
 //! ## Searching for elements
 //!
 //! We provide multiple ways to input sequences, as shown below:
-//! ```rust
+//! ```rust,no_run
 //! use placecare::io::RecordDesc;
+//! use std::fs::File;
 //!
 //! let input = vec![RecordDesc::new("Gh_01", "TTATAGACTCGATGGCCGCGCGG")];
-//! let input = RecordDesc::from_file("./input.fasta");
+//! let input = RecordDesc::from_file("./input.fasta").unwrap();
 //! let input = RecordDesc::from_string("\
 //! >Gh_01
 //! ATATCCGGATGGCATGCTGATC
-//! ");
-//! let input = RecordDesc::from_records(bio::io::fasta::Reader::new("./input.fasta"));
+//! ").unwrap();
+//! let input = RecordDesc::from_records(
+//!     bio::io::fasta::Reader::from_file("./input.fasta").unwrap().records(),
+//! ).unwrap();
 //!
-//! let mut f = File::open("input.txt").unwrap();
-//! let input = RecordDesc::from_reader(f);
+//! let f = File::open("input.txt").unwrap();
+//! let input = RecordDesc::from_reader(f).unwrap();
 //! ```
 //!
 //! Then we can search:
 //! ```rust
+//! use placecare::io::RecordDesc;
+//! use placecare::place_search::Search;
+//!
+//! let input = RecordDesc::from_string(">Gh_01\nATATCCGGATGGCATGCTGATC\n").unwrap();
+//!
+//! // Search on a single sequence
+//! let result = Search::search_elements_single_seq(&input[0]).unwrap();
+//!
+//! // Search on multiple sequences
+//! let result = Search::search_elements(&input).unwrap();
+//! ```
+//!
+//! A database can also be loaded at runtime, from a `place.seq` flat file
+//! or a serialized `.db` file, and searched instead of the embedded one:
+//! ```rust,no_run
+//! use placecare::db::Database;
+//! use placecare::io::RecordDesc;
+//! use placecare::place_search::{Search, SearchOptions};
+//!
+//! let db = Database::from_file("./my_place.seq").unwrap();
+//! let input = RecordDesc::from_file("./input.fasta").unwrap();
+//! let result = Search::search_elements_with(&db, &input, &SearchOptions::default()).unwrap();
+//! ```
+//!
+//! Large inputs, like whole genomes, can be streamed in chunks with bounded memory,
+//! the hits are given to a callback as they're found:
+//! ```rust,no_run
 //! use placecare::place_search::Search;
+//! use std::io::BufReader;
+//!
+//! let reader = BufReader::new(std::fs::File::open("./genome.fasta").unwrap());
+//! Search::search_stream(reader, |hit| {
+//!     print!("{}", hit);
+//!     Ok(())
+//! })
+//! .unwrap();
+//! ```
 //!
-//! // Search for a single element
-//! let result = Search::search_elements_single_seq(input).unwrap();
+//! The reported elements can be restricted with a filter:
+//! ```rust
+//! use placecare::filter::ElementFilter;
+//! use placecare::io::RecordDesc;
+//! use placecare::place_search::{Search, SearchOptions};
 //!
-//! // Search for multiple elements
-//! let result = Search::search_elements(input).unwrap();
+//! let input = RecordDesc::from_string(">Gh_01\nATATCCGGATGGCATGCTGATC\n").unwrap();
+//! let options = SearchOptions {
+//!     filter: ElementFilter {
+//!         keyword: Some("auxin".to_string()),
+//!         min_len: Some(6),
+//!         ..Default::default()
+//!     },
+//!     ..Default::default()
+//! };
+//! let result = Search::search_elements_with(&placecare::db::PLACE_DB, &input, &options).unwrap();
+//! ```
+//!
+//! The results borrow from the database and the query,
+//! they can be copied into owned ones which serialize with `serde`,
+//! with positions in the coordinate system of your choice:
+//! ```rust
+//! use placecare::coords::CoordinateSystem;
+//! use placecare::io::{OwnedSearchResult, RecordDesc};
+//! use placecare::place_search::Search;
+//!
+//! let input = RecordDesc::from_string(">Gh_01\nATATCCGGATGGCATGCTGATC\n").unwrap();
+//! let result = Search::search_elements(&input).unwrap();
+//! let owned: Vec<OwnedSearchResult> = result
+//!     .iter()
+//!     .map(|r| {
+//!         r.to_owned_result(CoordinateSystem::OneBasedClosed)
+//!             .with_metadata(&placecare::db::PLACE_DB)
+//!     })
+//!     .collect();
+//! let json = serde_json::to_string(&owned).unwrap();
+//! ```
+//!
+//! The promoters of a genome can be extracted from its GFF3/GTF annotation first:
+//! ```rust
+//! use placecare::io::RecordDesc;
+//! use placecare::place_search::Search;
+//! use placecare::promoters::{self, Annotation, AnnotationFormat, FeatureLevel, PromoterOptions};
+//!
+//! let genome = RecordDesc::from_string(">chr1\nAAACCCTAACTGTCTCAATGGATAAGACCGT\n").unwrap();
+//! let gff3 = "chr1\t.\tgene\t27\t31\t.\t+\t.\tID=gene1\n";
+//! let annotation =
+//!     Annotation::from_reader(gff3.as_bytes(), AnnotationFormat::Gff3, FeatureLevel::Gene)
+//!         .unwrap();
+//! let options = PromoterOptions { upstream: 20, ..Default::default() };
+//! let promoters = promoters::extract(&genome, &annotation, &options).unwrap();
+//! let records: Vec<RecordDesc> = promoters.into_iter().map(|p| p.record).collect();
+//! let result = Search::search_elements(&records).unwrap();
 //! ```
 //!
 //! ## Query
@@ -43,17 +130,25 @@
 //! We can query the PLACE databse using the following methods:
 //!
 //! ```rust
+//! use placecare::place_desc::SeqDesc;
 //! use placecare::place_search::Search;
 //!
 //! // The function will return a vector of Option<SeqDesc>
 //! // for which is a result of the input sequence.
-//! let e1: Vec<Option<SeqDesc>> = Search::query_elements_by_id(&vec!["TATABOX1", "TATABOX2"]);
-//! let e2: Vec<Option<SeqDesc>> = Search::query_elements_by_ac(&vec!["S000023", "S000260"]);
+//! let e1: Vec<Option<SeqDesc>> = Search::query_elements_by_id(&["TATABOX1", "TATABOX2"]);
+//! let e2: Vec<Option<SeqDesc>> = Search::query_elements_by_ac(&["S000023", "S000260"]);
+//!
+//! // Or by keywords, organism and description, see `annotation` for the syntax.
+//! let e3: Vec<SeqDesc> =
+//!     Search::query_elements_by_annotation("kw:\"light regulation\" AND os:arabidopsis").unwrap();
 //! ```
 
 /// Description structure of PLACE database.
 pub mod place_desc;
 
+/// Reader and writer of the `place.seq` flat file.
+pub mod parser;
+
 /// I/O Format
 pub mod io;
 
@@ -62,3 +157,44 @@ pub mod place_search;
 
 /// Maintains the PLACE database.
 pub mod db;
+
+/// Pattern matchers compiled from the PLACE database.
+pub mod matcher;
+
+/// Keyword, organism and full-text search over PLACE annotations.
+pub mod annotation;
+
+/// Filters restricting the elements a search reports.
+pub mod filter;
+
+/// BED6 / GFF3 / JSON writers of the search hits.
+pub mod output;
+
+/// Validation and sanitisation of the input sequences.
+pub mod validate;
+
+/// The error type of the library.
+pub mod error;
+
+/// Strands and coordinate systems of the hits.
+pub mod coords;
+
+/// Promoter extraction from a genome FASTA and a GFF3/GTF annotation.
+pub mod promoters;
+
+/// Over-representation of the elements in a set of sequences against a background.
+pub mod enrichment;
+
+/// Shuffled and Markov backgrounds, for the expected hits of the elements in a sequence.
+pub mod background;
+
+/// Query-by-element matrices of the hits, as TSV or Matrix Market.
+pub mod matrix;
+
+/// Density of the hits in sliding windows along the queries.
+pub mod density;
+
+/// Positional histograms of the elements around the TSS, and their bias towards it.
+pub mod positional;
+
+pub use error::{Error, Result};
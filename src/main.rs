@@ -1,54 +1,166 @@
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::*;
+use placecare::background::{self, Background, BackgroundModel, MAX_MARKOV_ORDER, MarkovModel};
+use placecare::coords::{CoordinateSystem, RegionConvention};
+use placecare::db::{Database, PLACE_DB};
+use placecare::density::{self, DensityFormat, DensityGroup, DensityOptions};
+use placecare::enrichment::{self, EnrichmentTest};
+use placecare::filter::{self, ElementFilter};
 use placecare::io::{RecordDesc, SearchResult};
+use placecare::matcher::AmbiguityPolicy;
+use placecare::matrix::{HitMatrix, MatrixColumns, MatrixValue};
+use placecare::output::{HitWriter, OutputFormat};
 use placecare::place_desc::SeqDesc;
-use placecare::place_search;
+use placecare::place_search::{self, SearchOptions};
+use placecare::positional::{self, PositionalOptions};
+use placecare::promoters::{
+    self, Annotation, AnnotationFormat, FeatureLevel, Promoter, PromoterOptions,
+};
+use placecare::validate::{GapMode, Sanitizer, ValidationPolicy};
+use regex::Regex;
 
 fn main() {
     let args = Cli::parse();
 
     match args.command {
         Commands::Search {
-            input,
-            input_seq,
-            print,
-            write,
-            outfile,
+            inputs,
+            search,
+            validation,
+            tss,
+            lift,
+            stream,
+            format,
+            coordinates,
+            metadata,
+            matrix,
+            output,
         } => {
+            let (custom_db, options) = search.build();
+            let db = custom_db.as_ref().unwrap_or(&PLACE_DB);
             let mut res = vec![];
-            
-            let inputs: Vec<RecordDesc>;
-            let single_input: RecordDesc;
+            let outfile = output.path("output.txt").display().to_string();
 
-            if let Some(input) = input {
-                println!("Input file: {}", input);
-                inputs = RecordDesc::from_file(&input);
-                res.extend(
-                    place_search::Search::search_elements(&inputs)
-                        .expect("Error: Failed to search elements"),
-                );
-            } else if let Some(input_seq) = input_seq {
-                println!("Input sequence: {}", input_seq);
-                single_input = RecordDesc::new("GhInput", &input_seq);
-                res.extend(
-                    place_search::Search::search_elements_single_seq(&single_input)
-                        .expect("Error: Failed to search elements"),
+            // Only the TSV output has the status lines on stdout,
+            // the other formats are kept clean to be loaded as they are
+            let status = |line: String| match (format, matrix.matrix) {
+                (Format::Tsv, None) => println!("{}", line),
+                _ => eprintln!("{}", line),
+            };
+
+            if stream && let Some(input) = &inputs.input {
+                status(format!("Input file: {}", input));
+                let format = format.into();
+                let coordinates = coordinates.into();
+                if let Err(e) = stream_search(
+                    db,
+                    input,
+                    &options,
+                    format,
+                    coordinates,
+                    metadata,
+                    output.write,
+                    &outfile,
+                ) {
+                    exit_with(e);
+                }
+                if output.write {
+                    status(format!("done at: {}", outfile));
+                }
+                return;
+            }
+
+            let raw_inputs = inputs.read(status).unwrap_or_default();
+            // The anchors of the FASTA headers win over `--tss`
+            let raw_inputs: Vec<RecordDesc> = match tss {
+                Some(tss) => raw_inputs
+                    .into_iter()
+                    .map(|record| match record.anchor() {
+                        Some(_) => record,
+                        None => {
+                            let anchor = tss.anchor(&record);
+                            record.with_anchor(anchor)
+                        }
+                    })
+                    .collect(),
+                None => raw_inputs,
+            };
+            let raw_inputs: Vec<RecordDesc> = match lift {
+                Some(convention) => raw_inputs
+                    .into_iter()
+                    .map(|record| {
+                        let record = record
+                            .with_header_region(convention.into())
+                            .unwrap_or_else(|e| exit_with(e));
+                        if record.source().is_none() {
+                            eprintln!("Warning: no region in the header of {}", record.id());
+                        }
+                        record
+                    })
+                    .collect(),
+                None => raw_inputs,
+            };
+
+            let sanitizer = validation.build();
+            let (inputs, reports) = sanitizer
+                .sanitize_all(&raw_inputs)
+                .unwrap_or_else(|e| exit_with(e));
+            for report in &reports {
+                match sanitizer.policy {
+                    ValidationPolicy::Clean => eprintln!("Masked with N: {}", report),
+                    _ => eprintln!("Warning: {}", report),
+                }
+            }
+
+            res.extend(
+                place_search::Search::search_elements_with(db, &inputs, &options)
+                    .unwrap_or_else(|e| exit_with(e)),
+            );
+            if lift.is_some() {
+                res = res.into_iter().map(|result| result.lift()).collect();
+            }
+
+            if let Some(value) = matrix.matrix {
+                let hits = HitMatrix::from_results(
+                    db,
+                    &res,
+                    matrix.matrix_columns.into(),
+                    value.into(),
+                    matrix.all_columns,
                 );
+                if let Err(e) = write_matrix(
+                    &hits,
+                    matrix.matrix_format,
+                    output.print,
+                    output.write,
+                    &outfile,
+                ) {
+                    exit_with(e);
+                }
+                if output.write {
+                    eprintln!("done at: {}", outfile);
+                }
+                return;
             }
 
-            let output = print_search(res);
-            if print {
-                println!("{}", output);
-            } else if write {
-                let path = PathBuf::from(&outfile);
-                if let Err(e) = write_content(path, output) {
-                    eprintln!("Error writing to file: {}", e);
-                } else {
-                    println!("done at: {}", outfile);
+            let content = match format {
+                Format::Tsv => print_search(res, coordinates.into()),
+                _ => format_search(db, &res, format.into(), coordinates.into(), metadata)
+                    .unwrap_or_else(|e| exit_with(e)),
+            };
+            if output.print {
+                match format {
+                    Format::Tsv => println!("{}", content),
+                    _ => print!("{}", content),
+                }
+            } else if output.write {
+                if let Err(e) = write_content(PathBuf::from(&outfile), content) {
+                    exit_with(format!("{}: {}", outfile, e));
                 }
+                println!("done at: {}", outfile);
             }
         }
         Commands::Query {
@@ -56,16 +168,19 @@ fn main() {
             input_text,
             id,
             ac,
-            print,
-            write,
-            outfile,
+            annotation,
+            db,
+            output,
         } => {
+            let custom_db = load_db(db);
+            let db = custom_db.as_ref().unwrap_or(&PLACE_DB);
             let mut inputs = vec![];
             let mut res = vec![];
 
             if let Some(input) = input {
                 println!("Input file: {}", input);
-                let input = fs::read_to_string(input).expect("Error: Failed to read file");
+                let input = fs::read_to_string(&input)
+                    .unwrap_or_else(|e| exit_with(format!("{}: {}", input, e)));
                 let input = input.lines().collect::<Vec<_>>();
                 for x in input {
                     inputs.push(x.to_string());
@@ -77,37 +192,393 @@ fn main() {
             let inputs = inputs.iter().map(|x| x.as_str()).collect::<Vec<_>>();
 
             if id {
-                res.extend(place_search::Search::query_elements_by_id(&inputs));
+                res.extend(place_search::Search::query_elements_by_id_with(db, &inputs));
             } else if ac {
-                res.extend(place_search::Search::query_elements_by_ac(&inputs));
+                res.extend(place_search::Search::query_elements_by_ac_with(db, &inputs));
+            } else if annotation {
+                for query in &inputs {
+                    let found = place_search::Search::query_elements_by_annotation_with(db, query)
+                        .unwrap_or_else(|e| exit_with(e));
+                    if found.is_empty() {
+                        res.push(None);
+                    }
+                    res.extend(found.into_iter().map(Some));
+                }
             }
 
-            let output = print_query(res);
-            if print {
-                println!("{}", output);
-            } else if write {
-                let path = PathBuf::from(&outfile);
-                if let Err(e) = write_content(path, output) {
-                    eprintln!("Error writing to file: {}", e);
-                } else {
-                    println!("done at: {}", outfile);
+            let content = print_query(res);
+            if output.print {
+                println!("{}", content);
+            } else if output.write {
+                let outfile = output.path("output_query.txt");
+                if let Err(e) = write_content(outfile.clone(), content) {
+                    exit_with(format!("{}: {}", outfile.display(), e));
                 }
-                println!("Output method: write to {}", outfile);
+                println!("done at: {}", outfile.display());
+                println!("Output method: write to {}", outfile.display());
+            }
+        }
+        Commands::Promoters {
+            genome,
+            annotation,
+            promoter,
+            output,
+        } => {
+            let found = extract_promoters(&genome, &annotation, &promoter);
+            output.emit("promoters.fa", |out| promoters::write_fasta(&found, out));
+        }
+        Commands::Enrich {
+            input,
+            genes,
+            background,
+            genome,
+            annotation,
+            promoter,
+            test,
+            max_q,
+            search,
+            output,
+        } => {
+            let (custom_db, options) = search.build();
+            let db = custom_db.as_ref().unwrap_or(&PLACE_DB);
+            let bg_records: Vec<RecordDesc> = match (background, genome, annotation) {
+                (Some(background), _, _) => {
+                    eprintln!("Background: {}", background);
+                    RecordDesc::from_file(&background).unwrap_or_else(|e| exit_with(e))
+                }
+                (None, Some(genome), Some(annotation)) => {
+                    extract_promoters(&genome, &annotation, &promoter)
+                        .into_iter()
+                        .map(|promoter| promoter.record)
+                        .collect()
+                }
+                _ => exit_with("a background is needed, --background or --genome and --annotation"),
+            };
+            let bg_results = search_or_exit(db, &bg_records, &options);
+
+            // The genes are taken from the background, which is searched once
+            let fg_records: Vec<RecordDesc>;
+            let (fg_results, default_test) = if let Some(genes) = genes {
+                eprintln!("Genes: {}", genes);
+                let names: std::collections::HashSet<String> = filter::read_names(&genes)
+                    .unwrap_or_else(|e| exit_with(e))
+                    .into_iter()
+                    .collect();
+                let fg_results: Vec<SearchResult> = bg_results
+                    .iter()
+                    .filter(|result| names.contains(&result.id))
+                    .cloned()
+                    .collect();
+                if fg_results.len() < names.len() {
+                    eprintln!(
+                        "Warning: {} of {} genes have no promoter",
+                        names.len() - fg_results.len(),
+                        names.len()
+                    );
+                }
+                (fg_results, Test::Hypergeometric)
+            } else if let Some(input) = input {
+                eprintln!("Input file: {}", input);
+                fg_records = RecordDesc::from_file(&input).unwrap_or_else(|e| exit_with(e));
+                (search_or_exit(db, &fg_records, &options), Test::Fisher)
+            } else {
+                exit_with("a foreground is needed, --input or --genes");
+            };
+
+            let test = test.unwrap_or(default_test).into();
+            let mut result = enrichment::enrich(&fg_results, &bg_results, test);
+            if let Some(max_q) = max_q {
+                result.elements.retain(|element| element.q_value <= max_q);
+            }
+            output.emit("enrichment.tsv", |out| write!(out, "{}", result));
+        }
+        Commands::Background {
+            inputs,
+            model,
+            order,
+            train,
+            permutations,
+            seed,
+            max_p,
+            search,
+            output,
+        } => {
+            let (custom_db, options) = search.build();
+            let db = custom_db.as_ref().unwrap_or(&PLACE_DB);
+            let records = inputs.read_required();
+
+            let model = match (model, train) {
+                (Model::Shuffle, _) => BackgroundModel::Shuffle,
+                (Model::Markov, Some(train)) => {
+                    eprintln!("Training on: {}", train);
+                    let reader = fs::File::open(&train)
+                        .map(std::io::BufReader::new)
+                        .unwrap_or_else(|e| exit_with(e));
+                    BackgroundModel::Markov(
+                        MarkovModel::train_reader(reader, order).unwrap_or_else(|e| exit_with(e)),
+                    )
+                }
+                (Model::Markov, None) => BackgroundModel::Markov(
+                    MarkovModel::train(&records, order).unwrap_or_else(|e| exit_with(e)),
+                ),
+            };
+            let background = Background {
+                model,
+                permutations,
+                seed,
+            };
+
+            let mut result = background::expected_counts(db, &records, &options, &background)
+                .unwrap_or_else(|e| exit_with(e));
+            if let Some(max_p) = max_p {
+                result.elements.retain(|element| element.p_value <= max_p);
+            }
+            output.emit("background.tsv", |out| write!(out, "{}", result));
+        }
+        Commands::Density {
+            inputs,
+            window,
+            step,
+            group,
+            format,
+            search,
+            output,
+        } => {
+            let (custom_db, options) = search.build();
+            let db = custom_db.as_ref().unwrap_or(&PLACE_DB);
+            let records = inputs.read_required();
+
+            let density_options = DensityOptions {
+                window,
+                step,
+                group: group.into(),
+            };
+            let results = search_or_exit(db, &records, &options);
+            let tracks = density::density(db, &records, &results, &density_options)
+                .unwrap_or_else(|e| exit_with(e));
+
+            output.emit("density.tsv", |out| {
+                density::write_tracks(&tracks, format.into(), &density_options, out)
+            });
+        }
+        Commands::Positional {
+            input,
+            genome,
+            annotation,
+            promoter,
+            tss,
+            bin,
+            near,
+            min_hits,
+            max_q,
+            histograms,
+            svg,
+            svg_plots,
+            search,
+            output,
+        } => {
+            let (custom_db, options) = search.build();
+            let db = custom_db.as_ref().unwrap_or(&PLACE_DB);
+            let records: Vec<RecordDesc> = match (input, genome, annotation) {
+                (Some(input), _, _) => {
+                    eprintln!("Input file: {}", input);
+                    RecordDesc::from_file(&input).unwrap_or_else(|e| exit_with(e))
+                }
+                (None, Some(genome), Some(annotation)) => {
+                    extract_promoters(&genome, &annotation, &promoter)
+                        .into_iter()
+                        .map(|promoter| promoter.record)
+                        .collect()
+                }
+                _ => exit_with("an input is needed, --input or --genome and --annotation"),
+            };
+            // The anchors of the FASTA headers win over `--tss`
+            let records: Vec<RecordDesc> = records
+                .into_iter()
+                .map(|record| match (record.anchor(), tss) {
+                    (None, Some(tss)) => {
+                        let anchor = tss.anchor(&record);
+                        record.with_anchor(anchor)
+                    }
+                    _ => record,
+                })
+                .collect();
+
+            let results = search_or_exit(db, &records, &options);
+            let positional_options = PositionalOptions {
+                bin,
+                near,
+                min_hits,
+            };
+            let mut result = positional::positional(&records, &results, &positional_options);
+            if result.queries < records.len() {
+                eprintln!(
+                    "Warning: {} of {} sequences have no TSS, see --tss",
+                    records.len() - result.queries,
+                    records.len()
+                );
+            }
+            if result.queries == 0 {
+                exit_with("no sequence has a TSS");
+            }
+            if let Some(max_q) = max_q {
+                result.elements.retain(|element| element.q_value <= max_q);
+            }
+
+            if let Some(path) = histograms
+                && let Err(e) = create_file(Path::new(&path))
+                    .and_then(|mut file| result.write_histograms(&mut file).and(file.flush()))
+            {
+                exit_with(format!("{}: {}", path, e));
+            }
+            if let Some(path) = svg
+                && let Err(e) = create_file(Path::new(&path))
+                    .and_then(|mut file| result.write_svg(&mut file, svg_plots).and(file.flush()))
+            {
+                exit_with(format!("{}: {}", path, e));
             }
+            output.emit("positional.tsv", |out| write!(out, "{}", result));
         }
     }
 }
 
-fn print_search(res: Vec<SearchResult>) -> String {
+/// Extract the promoters of the genes of a genome, exiting on errors.
+fn extract_promoters(genome: &str, annotation: &str, args: &PromoterArgs) -> Vec<Promoter> {
+    eprintln!("Genome: {}", genome);
+    let genome = RecordDesc::from_file(genome).unwrap_or_else(|e| exit_with(e));
+    eprintln!("Annotation: {}", annotation);
+    let level = args.level.into();
+    let annotation = match args.annotation_format {
+        Some(format) => Annotation::from_file_as(annotation, format.into(), level),
+        None => Annotation::from_file(annotation, level),
+    }
+    .unwrap_or_else(|e| exit_with(e));
+
+    let found =
+        promoters::extract(&genome, &annotation, &args.build()).unwrap_or_else(|e| exit_with(e));
+    eprintln!(
+        "Promoters: {} of {} features",
+        found.len(),
+        annotation.features.len()
+    );
+    found
+}
+
+/// Search the records, exiting on errors.
+fn search_or_exit<'a>(
+    db: &'a Database,
+    records: &'a [RecordDesc],
+    options: &SearchOptions,
+) -> Vec<SearchResult<'a>> {
+    place_search::Search::search_elements_with(db, records, options)
+        .unwrap_or_else(|e| exit_with(e))
+}
+
+/// Load the database given by `--db`, the embedded one is used without it.
+fn load_db(path: Option<String>) -> Option<Database> {
+    let path = path?;
+    eprintln!("Database: {}", path);
+    Some(Database::from_file(&path).unwrap_or_else(|e| exit_with(e)))
+}
+
+/// Create a file to write to, and the directories it's in.
+fn create_file(path: &Path) -> std::io::Result<std::io::BufWriter<fs::File>> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(std::io::BufWriter::new(fs::File::create(path)?))
+}
+
+/// Print the error and exit.
+fn exit_with<E: std::fmt::Display>(e: E) -> ! {
+    eprintln!("Error: {}", e);
+    std::process::exit(1);
+}
+
+/// Search a FASTA file with bounded memory, writing the hits as they're found.
+#[allow(clippy::too_many_arguments)]
+fn stream_search(
+    db: &Database,
+    input: &str,
+    options: &SearchOptions,
+    format: OutputFormat,
+    coordinates: CoordinateSystem,
+    metadata: bool,
+    write: bool,
+    outfile: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let reader = std::io::BufReader::new(fs::File::open(input)?);
+    let out: Box<dyn Write> = if write {
+        Box::new(create_file(Path::new(outfile))?)
+    } else {
+        Box::new(std::io::BufWriter::new(std::io::stdout().lock()))
+    };
+
+    let mut out = HitWriter::new(out, format)?.with_coordinates(coordinates);
+    if metadata {
+        out = out.with_metadata(db);
+    }
+    place_search::Search::search_stream_with(db, reader, options, |hit| out.write(&hit))?;
+    out.finish()?;
+    Ok(())
+}
+
+/// Write the hits in one of the formats of `HitWriter`.
+fn format_search(
+    db: &Database,
+    res: &[SearchResult],
+    format: OutputFormat,
+    coordinates: CoordinateSystem,
+    metadata: bool,
+) -> std::io::Result<String> {
+    let mut out = HitWriter::new(vec![], format)?.with_coordinates(coordinates);
+    if metadata {
+        out = out.with_metadata(db);
+    }
+    out.write_all(res)?;
+    Ok(String::from_utf8_lossy(&out.finish()?).into_owned())
+}
+
+/// Print or write a matrix, with its row and column names next to a Matrix Market file.
+fn write_matrix(
+    matrix: &HitMatrix,
+    format: MatrixFormat,
+    print: bool,
+    write: bool,
+    outfile: &str,
+) -> std::io::Result<()> {
+    let write_to = |out: &mut dyn Write| match format {
+        MatrixFormat::Tsv => matrix.write_tsv(out),
+        MatrixFormat::Mtx => matrix.write_matrix_market(out),
+    };
+    if print {
+        write_to(&mut std::io::stdout().lock())?;
+    } else if write {
+        let mut file = create_file(Path::new(outfile))?;
+        write_to(&mut file)?;
+        file.flush()?;
+        if format == MatrixFormat::Mtx {
+            let rows = fs::File::create(format!("{}.rows", outfile))?;
+            HitMatrix::write_names(&matrix.rows, std::io::BufWriter::new(rows))?;
+            let columns = fs::File::create(format!("{}.cols", outfile))?;
+            HitMatrix::write_names(&matrix.columns, std::io::BufWriter::new(columns))?;
+        }
+    }
+    Ok(())
+}
+
+fn print_search(res: Vec<SearchResult>, coordinates: CoordinateSystem) -> String {
     let mut output = String::new();
     for x in res.clone() {
         output.push_str(&format!("ID: {}\t", x.id));
         output.push_str(&format!("Count: {}\t", x.count));
-        output.push_str("\n");
+        output.push('\n');
     }
     for x in res {
         let seqs = placecare::io::SearchedDescList::from(x.search_descs);
-        output.push_str(&format!("{}\n", seqs));
+        output.push_str(&format!("{}\n", seqs.table(coordinates)));
     }
     output
 }
@@ -119,7 +590,9 @@ fn print_query(res: Vec<Option<SeqDesc>>) -> String {
             output.push_str(&format!("ID: {}\t", x.id));
             output.push_str(&format!("Accession: {}\t", x.ac));
             output.push_str(&format!("Description: {}\t", x.de));
-            output.push_str("\n");
+            let refs: Vec<String> = x.refs.iter().map(|r| r.to_string()).collect();
+            output.push_str(&format!("References: {}\t", refs.join(" | ")));
+            output.push('\n');
         } else {
             output.push_str(&format!("No result found<No.{}>\n", i));
         }
@@ -133,10 +606,10 @@ fn write_content(path: PathBuf, content: String) -> Result<(), std::io::Error> {
         let mut file = fs::File::create(file_path)?;
         file.write_all(content.as_bytes())?;
     } else {
-        if let Some(parent) = path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)?;
-            }
+        if let Some(parent) = path.parent()
+            && !parent.exists()
+        {
+            fs::create_dir_all(parent)?;
         }
         let mut file = fs::File::create(path)?;
         file.write_all(content.as_bytes())?;
@@ -162,44 +635,67 @@ struct Cli {
 enum Commands {
     #[command(name = "search", about = "Search for elements")]
     Search {
-        // Input method: default is file
-        // 0. file - to read from a file
-        // 1. seq - to read from a sequence
+        #[command(flatten)]
+        inputs: InputArgs,
+
+        #[command(flatten)]
+        search: SearchArgs,
+
+        #[command(flatten)]
+        validation: ValidationArgs,
+
         #[arg(
-            short = 'i',
             long,
-            conflicts_with("input_seq"),
-            help = "Input file path",
-            group = "inputs"
+            value_parser = parse_tss,
+            conflicts_with("stream"),
+            help = "1-based TSS or ATG of the queries, or 'end' right after them, \
+                    to report the hits relative to it; tss=<position> in a FASTA header wins"
         )]
-        input: Option<String>,
+        tss: Option<Tss>,
 
         #[arg(
-            short = 's',
             long,
-            conflicts_with("input"),
-            help = "Input sequence",
-            group = "inputs"
+            value_enum,
+            conflicts_with("stream"),
+            help = "Lift the hits to the genome, from regions like chr1:1000-3000(-) in the FASTA headers"
         )]
-        input_seq: Option<String>,
+        lift: Option<Regions>,
 
-        // Output method: default is print
-        // 0. print - to print to stdout
-        // 1. write - to write to a file uses the input path
-        #[arg(short = 'p', long, help = "Output method: print")]
-        print: bool,
+        #[arg(
+            long,
+            requires("input"),
+            help = "Scan the input file in chunks and output the hits as they're found"
+        )]
+        stream: bool,
+
+        #[arg(
+            short = 'f',
+            long,
+            value_enum,
+            default_value_t = Format::Tsv,
+            help = "Output format of the hits"
+        )]
+        format: Format,
 
-        #[arg(short = 'w', long, help = "Output method: write")]
-        write: bool,
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = Coordinates::OneBased,
+            help = "Coordinate system of the TSV and JSON positions, BED and GFF3 keep their own"
+        )]
+        coordinates: Coordinates,
 
         #[arg(
-            short = 'o',
             long,
-            required_if_eq("write", "true"),
-            default_value = "output.txt",
-            help = "Output file path"
+            help = "Add the keywords and organism of the elements to the JSON output"
         )]
-        outfile: String,
+        metadata: bool,
+
+        #[command(flatten)]
+        matrix: MatrixArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
     },
 
     #[command(name = "query", about = "Query the PLACE database")]
@@ -210,7 +706,7 @@ enum Commands {
         #[arg(
             short = 'i',
             long,
-            conflicts_with("input_seq"),
+            conflicts_with("input_text"),
             help = "Input file path",
             group = "inputs"
         )]
@@ -228,33 +724,926 @@ enum Commands {
         // Query method: default is id
         // 0. id - to query by id
         // 1. ac - to query by accession
-        #[arg(short = 'q', long, conflicts_with("ac"), help = "Query method: id")]
+        // 2. annotation - to query by keywords, organism and description
+        #[arg(
+            short = 'q',
+            long,
+            conflicts_with_all(["ac", "annotation"]),
+            help = "Query method: id"
+        )]
         id: bool,
 
         #[arg(
             short = 'a',
             long,
-            conflicts_with("id"),
+            conflicts_with_all(["id", "annotation"]),
             help = "Query method: accession"
         )]
         ac: bool,
 
-        // Output method: default is print
-        // 0. print - to print to stdout
-        // 1. write - to write to a file uses the input path
-        #[arg(short = 'p', long, help = "Output method: print")]
-        print: bool,
+        #[arg(
+            short = 't',
+            long,
+            conflicts_with_all(["id", "ac"]),
+            help = "Query method: annotation, e.g. 'kw:\"light regulation\" AND os:arabidopsis'"
+        )]
+        annotation: bool,
+
+        #[arg(
+            short = 'd',
+            long,
+            help = "Database file to use instead of the embedded one (place.seq or .db)"
+        )]
+        db: Option<String>,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    #[command(
+        name = "promoters",
+        about = "Extract the promoters of the genes of a genome, as FASTA"
+    )]
+    Promoters {
+        #[arg(short = 'g', long, help = "Genome FASTA file")]
+        genome: String,
+
+        #[arg(short = 'a', long, help = "GFF3 or GTF annotation of the genome")]
+        annotation: String,
+
+        #[command(flatten)]
+        promoter: PromoterArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    #[command(
+        name = "enrich",
+        about = "Test the over-representation of the elements in sequences against a background"
+    )]
+    Enrich {
+        #[arg(
+            short = 'i',
+            long,
+            help = "Foreground FASTA file, e.g. the promoters of DEGs",
+            group = "foreground"
+        )]
+        input: Option<String>,
+
+        #[arg(
+            long,
+            requires("genome"),
+            help = "Foreground gene IDs, one per line, their promoters are taken from --genome",
+            group = "foreground"
+        )]
+        genes: Option<String>,
+
+        #[arg(
+            short = 'b',
+            long,
+            conflicts_with("genome"),
+            help = "Background FASTA file"
+        )]
+        background: Option<String>,
+
+        #[arg(
+            short = 'g',
+            long,
+            requires("annotation"),
+            help = "Genome FASTA file, all its promoters are the background"
+        )]
+        genome: Option<String>,
+
+        #[arg(short = 'a', long, help = "GFF3 or GTF annotation of the genome")]
+        annotation: Option<String>,
 
-        #[arg(short = 'w', long, help = "Output method: write")]
-        write: bool,
+        #[command(flatten)]
+        promoter: PromoterArgs,
 
         #[arg(
-            short = 'o',
             long,
-            required_if_eq("write", "true"),
-            default_value = "output_query.txt",
-            help = "Output file path"
+            value_enum,
+            help = "Test of over-representation, hypergeometric with --genes and fisher otherwise by default"
         )]
-        outfile: String,
+        test: Option<Test>,
+
+        #[arg(long, help = "Only report the elements with a q-value up to this one")]
+        max_q: Option<f64>,
+
+        #[command(flatten)]
+        search: SearchArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
     },
+
+    #[command(
+        name = "background",
+        about = "Compare the hits of each sequence with shuffled or Markov random sequences"
+    )]
+    Background {
+        #[command(flatten)]
+        inputs: InputArgs,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = Model::Shuffle,
+            help = "How the random sequences are drawn"
+        )]
+        model: Model,
+
+        #[arg(
+            long,
+            default_value_t = 2,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new()
+                .range(0..=MAX_MARKOV_ORDER as u64),
+            help = "Order of the Markov model, up to 8"
+        )]
+        order: usize,
+
+        #[arg(
+            long,
+            help = "FASTA file to train the Markov model on, e.g. a genome, the input by default"
+        )]
+        train: Option<String>,
+
+        #[arg(
+            short = 'n',
+            long,
+            default_value_t = 100,
+            help = "Random sequences per input sequence"
+        )]
+        permutations: usize,
+
+        #[arg(long, default_value_t = 1, help = "Seed of the random sequences")]
+        seed: u64,
+
+        #[arg(long, help = "Only report the elements with a p-value up to this one")]
+        max_p: Option<f64>,
+
+        #[command(flatten)]
+        search: SearchArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    #[command(
+        name = "density",
+        about = "Count the hits in sliding windows along the sequences"
+    )]
+    Density {
+        #[command(flatten)]
+        inputs: InputArgs,
+
+        #[arg(long, default_value_t = 100, help = "Window length")]
+        window: usize,
+
+        #[arg(long, default_value_t = 50, help = "Bases between the window starts")]
+        step: usize,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = Group::All,
+            help = "Tracks of the hits"
+        )]
+        group: Group,
+
+        #[arg(
+            short = 'f',
+            long,
+            value_enum,
+            default_value_t = DensityFormatArg::Tsv,
+            help = "Output format of the tracks"
+        )]
+        format: DensityFormatArg,
+
+        #[command(flatten)]
+        search: SearchArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    #[command(
+        name = "positional",
+        about = "Histograms of the element positions around the TSS, and their bias towards it"
+    )]
+    Positional {
+        #[arg(
+            short = 'i',
+            long,
+            conflicts_with("genome"),
+            help = "Promoter FASTA file, with tss=<position> in the headers or --tss"
+        )]
+        input: Option<String>,
+
+        #[arg(
+            short = 'g',
+            long,
+            requires("annotation"),
+            help = "Genome FASTA file, its promoters are extracted with --annotation"
+        )]
+        genome: Option<String>,
+
+        #[arg(short = 'a', long, help = "GFF3 or GTF annotation of the genome")]
+        annotation: Option<String>,
+
+        #[command(flatten)]
+        promoter: PromoterArgs,
+
+        #[arg(
+            long,
+            value_parser = parse_tss,
+            help = "1-based TSS of the input sequences, or 'end' right after them; tss=<position> in a FASTA header wins"
+        )]
+        tss: Option<Tss>,
+
+        #[arg(long, default_value_t = 50, help = "Bases per bin")]
+        bin: usize,
+
+        #[arg(
+            long,
+            default_value_t = 100,
+            help = "Bases on each side of the TSS tested for more hits than a uniform spread"
+        )]
+        near: usize,
+
+        #[arg(
+            long,
+            default_value_t = 5,
+            help = "Only report the elements with at least this many hits"
+        )]
+        min_hits: usize,
+
+        #[arg(long, help = "Only report the elements with a q-value up to this one")]
+        max_q: Option<f64>,
+
+        #[arg(
+            long,
+            help = "Also write the histograms of the elements to this TSV file"
+        )]
+        histograms: Option<String>,
+
+        #[arg(long, help = "Also plot the histograms to this SVG file")]
+        svg: Option<String>,
+
+        #[arg(
+            long,
+            default_value_t = 12,
+            requires("svg"),
+            help = "Elements plotted in the SVG, by increasing p-value"
+        )]
+        svg_plots: usize,
+
+        #[command(flatten)]
+        search: SearchArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+}
+
+/// The queries of a command, from a FASTA file or a sequence.
+#[derive(Args)]
+struct InputArgs {
+    // Input method: default is file
+    // 0. file - to read from a file
+    // 1. seq - to read from a sequence
+    #[arg(
+        short = 'i',
+        long,
+        conflicts_with("input_seq"),
+        help = "Input file path",
+        group = "inputs"
+    )]
+    input: Option<String>,
+
+    #[arg(
+        short = 's',
+        long,
+        conflicts_with("input"),
+        help = "Input sequence",
+        group = "inputs"
+    )]
+    input_seq: Option<String>,
+}
+
+impl InputArgs {
+    /// Read the queries, exiting on errors, `None` without any input.
+    ///
+    /// The input is given to `status` before it's read.
+    fn read(&self, status: impl Fn(String)) -> Option<Vec<RecordDesc>> {
+        if let Some(input) = &self.input {
+            status(format!("Input file: {}", input));
+            Some(RecordDesc::from_file(input).unwrap_or_else(|e| exit_with(e)))
+        } else if let Some(input_seq) = &self.input_seq {
+            status(format!("Input sequence: {}", input_seq));
+            Some(vec![RecordDesc::new("GhInput", input_seq)])
+        } else {
+            None
+        }
+    }
+
+    /// Read the queries of a command which needs some.
+    fn read_required(&self) -> Vec<RecordDesc> {
+        self.read(|line| eprintln!("{}", line))
+            .unwrap_or_else(|| exit_with("an input is needed, --input or --input-seq"))
+    }
+}
+
+/// How the queries are searched, shared by the commands which search them.
+#[derive(Args)]
+struct SearchArgs {
+    // Mismatch tolerance: default is perfect matches only
+    #[arg(
+        short = 'm',
+        long,
+        default_value_t = 0,
+        help = "Mismatches allowed per element"
+    )]
+    mismatches: usize,
+
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Minimum element length to allow mismatches"
+    )]
+    mismatch_min_len: usize,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Ambiguity::Strict,
+        help = "How ambiguous bases (N, R, Y...) in the query are matched"
+    )]
+    ambiguity: Ambiguity,
+
+    #[command(flatten)]
+    filter: Box<FilterArgs>,
+
+    #[arg(
+        short = 'd',
+        long,
+        help = "Database file to use instead of the embedded one (place.seq or .db)"
+    )]
+    db: Option<String>,
+}
+
+impl SearchArgs {
+    /// Load the database given by `--db` and build the options of the run, exiting on errors.
+    ///
+    /// The embedded database is used when no database is returned.
+    fn build(self) -> (Option<Database>, SearchOptions) {
+        let db = load_db(self.db);
+        let options = SearchOptions {
+            max_mismatches: self.mismatches,
+            mismatch_min_len: self.mismatch_min_len,
+            ambiguity: self.ambiguity.into(),
+            filter: self.filter.build().unwrap_or_else(|e| exit_with(e)),
+        };
+        let unknown = options
+            .filter
+            .unknown_exclusions(db.as_ref().unwrap_or(&PLACE_DB));
+        if !unknown.is_empty() {
+            eprintln!(
+                "Warning: excluded elements not in the database: {}",
+                unknown.join(", ")
+            );
+        }
+        (db, options)
+    }
+}
+
+/// Where the output of a command goes.
+#[derive(Args)]
+struct OutputArgs {
+    // Output method: default is print
+    // 0. print - to print to stdout
+    // 1. write - to write to a file uses the input path
+    #[arg(short = 'p', long, help = "Output method: print")]
+    print: bool,
+
+    #[arg(short = 'w', long, help = "Output method: write")]
+    write: bool,
+
+    #[arg(
+        short = 'o',
+        long,
+        help = "Output file path, or its directory, the command names the file by default"
+    )]
+    outfile: Option<String>,
+}
+
+impl OutputArgs {
+    /// The file to write, `default` when it's missing or in the directory given.
+    fn path(&self, default: &str) -> PathBuf {
+        match &self.outfile {
+            Some(outfile) if Path::new(outfile).is_dir() => Path::new(outfile).join(default),
+            Some(outfile) => PathBuf::from(outfile),
+            None => PathBuf::from(default),
+        }
+    }
+
+    /// Print or write the output of a command, exiting on errors.
+    fn emit<F>(&self, default: &str, output: F)
+    where
+        F: FnOnce(&mut dyn Write) -> std::io::Result<()>,
+    {
+        if self.print {
+            let mut out = std::io::stdout().lock();
+            if let Err(e) = output(&mut out).and_then(|_| out.flush()) {
+                exit_with(e);
+            }
+        } else if self.write {
+            let path = self.path(default);
+            let written = create_file(&path).and_then(|mut file| {
+                output(&mut file)?;
+                file.flush()
+            });
+            if let Err(e) = written {
+                exit_with(format!("{}: {}", path.display(), e));
+            }
+            eprintln!("done at: {}", path.display());
+        }
+    }
+}
+
+/// How the promoters are extracted, for the commands reading a genome and its annotation.
+#[derive(Args)]
+struct PromoterArgs {
+    #[arg(
+        long,
+        value_enum,
+        help = "Format of the annotation, from its extension by default"
+    )]
+    annotation_format: Option<AnnotationFormatArg>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Level::Gene,
+        help = "Extract one promoter per gene or per transcript"
+    )]
+    level: Level,
+
+    #[arg(
+        short = 'u',
+        long,
+        default_value_t = 1000,
+        help = "Bases upstream of the TSS"
+    )]
+    upstream: usize,
+
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Bases downstream of the TSS, the TSS included"
+    )]
+    downstream: usize,
+
+    #[arg(
+        long,
+        help = "Stop the upstream region at the nearest neighbouring gene"
+    )]
+    stop_at_neighbor: bool,
+}
+
+impl PromoterArgs {
+    fn build(&self) -> PromoterOptions {
+        PromoterOptions {
+            upstream: self.upstream,
+            downstream: self.downstream,
+            stop_at_neighbor: self.stop_at_neighbor,
+        }
+    }
+}
+
+/// Element filters, shared by the commands which search the queries.
+#[derive(Args)]
+struct FilterArgs {
+    #[arg(long, value_delimiter = ',', help = "Only report these element IDs")]
+    ids: Vec<String>,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Only report these element accessions"
+    )]
+    acs: Vec<String>,
+
+    #[arg(long, help = "Only report elements with this keyword, e.g. auxin")]
+    keyword: Option<String>,
+
+    #[arg(long, help = "Only report elements from this organism")]
+    organism: Option<String>,
+
+    #[arg(long, help = "Minimum element length")]
+    min_len: Option<usize>,
+
+    #[arg(long, help = "Maximum element length")]
+    max_len: Option<usize>,
+
+    #[arg(long, help = "Only report elements whose ID matches this regex")]
+    id_regex: Option<String>,
+
+    #[arg(
+        long,
+        help = "File of element IDs or accessions to report, one per line"
+    )]
+    include_file: Option<String>,
+
+    #[arg(long, help = "File of element IDs or accessions to skip, one per line")]
+    exclude_file: Option<String>,
+}
+
+impl FilterArgs {
+    fn build(self) -> Result<ElementFilter, String> {
+        let mut filter = ElementFilter {
+            ids: self.ids.into_iter().collect(),
+            acs: self.acs.into_iter().collect(),
+            keyword: self.keyword,
+            organism: self.organism,
+            min_len: self.min_len,
+            max_len: self.max_len,
+            id_regex: None,
+            exclude: Default::default(),
+        };
+        if let Some(re) = self.id_regex {
+            filter.id_regex =
+                Some(Regex::new(&re).map_err(|e| format!("Invalid --id-regex: {}", e))?);
+        }
+        if let Some(path) = self.include_file {
+            filter = filter
+                .include_file(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        }
+        if let Some(path) = self.exclude_file {
+            filter = filter
+                .exclude_file(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        }
+        Ok(filter)
+    }
+}
+
+/// Matrix output of the `search` command, instead of the hits.
+#[derive(Args)]
+struct MatrixArgs {
+    #[arg(
+        long,
+        value_enum,
+        conflicts_with("stream"),
+        help = "Output a query-by-element matrix of these values instead of the hits"
+    )]
+    matrix: Option<MatrixCells>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MatrixColumnsArg::Elements,
+        requires("matrix"),
+        help = "Columns of the matrix"
+    )]
+    matrix_columns: MatrixColumnsArg,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MatrixFormat::Tsv,
+        requires("matrix"),
+        help = "Format of the matrix, mtx also writes the names in <outfile>.rows and <outfile>.cols"
+    )]
+    matrix_format: MatrixFormat,
+
+    #[arg(
+        long,
+        requires("matrix"),
+        help = "Keep a column for every element or keyword of the database, even without hits"
+    )]
+    all_columns: bool,
+}
+
+/// Input validation of the `search` command, its flags conflict with `--stream`.
+#[derive(Args)]
+struct ValidationArgs {
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Validation::Warn,
+        conflicts_with("stream"),
+        help = "What to do with characters which aren't IUPAC DNA codes"
+    )]
+    validation: Validation,
+
+    #[arg(
+        long,
+        conflicts_with("stream"),
+        help = "Convert U to T, for RNA sequences"
+    )]
+    rna_to_dna: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Gaps::Keep,
+        conflicts_with("stream"),
+        help = "What to do with the gaps (- and .) of the sequences"
+    )]
+    gaps: Gaps,
+}
+
+impl ValidationArgs {
+    fn build(self) -> Sanitizer {
+        Sanitizer {
+            policy: match self.validation {
+                Validation::Error => ValidationPolicy::Error,
+                Validation::Warn => ValidationPolicy::Warn,
+                Validation::Clean => ValidationPolicy::Clean,
+            },
+            rna_to_dna: self.rna_to_dna,
+            gaps: match self.gaps {
+                Gaps::Keep => GapMode::Keep,
+                Gaps::Strip => GapMode::Strip,
+                Gaps::Split => GapMode::Split,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Validation {
+    /// Stop with an error
+    Error,
+    /// Search the sequence as it is and print a warning
+    Warn,
+    /// Replace the characters with N and print a warning
+    Clean,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Gaps {
+    /// Keep the gaps, they're handled like other invalid characters
+    Keep,
+    /// Remove the gaps
+    Strip,
+    /// Split the sequences on the gaps into id:start-end pieces
+    Split,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Tab-separated table with a summary per sequence
+    Tsv,
+    /// BED6, 0-based half-open
+    Bed,
+    /// GFF3, 1-based closed
+    Gff3,
+    /// A JSON array of hits
+    Json,
+    /// One JSON hit per line
+    Jsonl,
+}
+
+impl From<Format> for OutputFormat {
+    fn from(value: Format) -> Self {
+        match value {
+            Format::Tsv => OutputFormat::Tsv,
+            Format::Bed => OutputFormat::Bed,
+            Format::Gff3 => OutputFormat::Gff3,
+            Format::Json => OutputFormat::Json,
+            Format::Jsonl => OutputFormat::JsonLines,
+        }
+    }
+}
+
+/// The anchor given by `--tss`.
+#[derive(Clone, Copy)]
+enum Tss {
+    /// 1-based position in the query
+    Position(isize),
+    /// Right after the last base, for regions upstream of the TSS
+    End,
+}
+
+impl Tss {
+    /// The 0-based anchor in a record.
+    fn anchor(self, record: &RecordDesc) -> isize {
+        match self {
+            Tss::Position(position) => position - 1,
+            Tss::End => record.len() as isize,
+        }
+    }
+}
+
+fn parse_tss(value: &str) -> Result<Tss, String> {
+    if value.eq_ignore_ascii_case("end") {
+        return Ok(Tss::End);
+    }
+    match value.parse::<isize>() {
+        Ok(position) if position > 0 => Ok(Tss::Position(position)),
+        _ => Err(format!(
+            "expected a position from 1 or 'end', found '{}'",
+            value
+        )),
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Test {
+    /// The foreground is part of the background, e.g. some genes of a genome
+    Hypergeometric,
+    /// The foreground and the background are distinct sets, one-sided Fisher's exact test
+    Fisher,
+}
+
+impl From<Test> for EnrichmentTest {
+    fn from(value: Test) -> Self {
+        match value {
+            Test::Hypergeometric => EnrichmentTest::Hypergeometric,
+            Test::Fisher => EnrichmentTest::Fisher,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Model {
+    /// Shuffles of each sequence keeping its dinucleotides
+    Shuffle,
+    /// A k-th order Markov model, see --order and --train
+    Markov,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Regions {
+    /// samtools faidx: chr1:1000-3000, 1-based closed, /rc on the minus strand
+    Samtools,
+    /// bedtools getfasta: chr1:999-3000, 0-based half-open, (-) with -s
+    Bedtools,
+}
+
+impl From<Regions> for RegionConvention {
+    fn from(value: Regions) -> Self {
+        match value {
+            Regions::Samtools => RegionConvention::Samtools,
+            Regions::Bedtools => RegionConvention::Bedtools,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AnnotationFormatArg {
+    Gff3,
+    Gtf,
+}
+
+impl From<AnnotationFormatArg> for AnnotationFormat {
+    fn from(value: AnnotationFormatArg) -> Self {
+        match value {
+            AnnotationFormatArg::Gff3 => AnnotationFormat::Gff3,
+            AnnotationFormatArg::Gtf => AnnotationFormat::Gtf,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MatrixCells {
+    /// Hits of each column
+    Counts,
+    /// 1 with a hit of the column, 0 otherwise
+    Presence,
+    /// Hits on each strand, in the columns ID(+) and ID(-)
+    Strand,
+}
+
+impl From<MatrixCells> for MatrixValue {
+    fn from(value: MatrixCells) -> Self {
+        match value {
+            MatrixCells::Counts => MatrixValue::Count,
+            MatrixCells::Presence => MatrixValue::Presence,
+            MatrixCells::Strand => MatrixValue::Strand,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MatrixColumnsArg {
+    /// One column per element
+    Elements,
+    /// One column per PLACE keyword, e.g. TATA box
+    Keywords,
+}
+
+impl From<MatrixColumnsArg> for MatrixColumns {
+    fn from(value: MatrixColumnsArg) -> Self {
+        match value {
+            MatrixColumnsArg::Elements => MatrixColumns::Elements,
+            MatrixColumnsArg::Keywords => MatrixColumns::Keywords,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MatrixFormat {
+    /// Dense tab-separated table, with a header line
+    Tsv,
+    /// Sparse Matrix Market coordinate file
+    Mtx,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Group {
+    /// One track of all the elements
+    All,
+    /// One track per element
+    Elements,
+    /// One track per PLACE keyword, e.g. TATA box
+    Keywords,
+}
+
+impl From<Group> for DensityGroup {
+    fn from(value: Group) -> Self {
+        match value {
+            Group::All => DensityGroup::All,
+            Group::Elements => DensityGroup::Elements,
+            Group::Keywords => DensityGroup::Keywords,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DensityFormatArg {
+    /// Tab-separated table of the windows, 1-based closed
+    Tsv,
+    /// bedGraph, 0-based half-open, hits per kb
+    Bedgraph,
+    /// WIG fixedStep, hits per kb
+    Wig,
+}
+
+impl From<DensityFormatArg> for DensityFormat {
+    fn from(value: DensityFormatArg) -> Self {
+        match value {
+            DensityFormatArg::Tsv => DensityFormat::Tsv,
+            DensityFormatArg::Bedgraph => DensityFormat::BedGraph,
+            DensityFormatArg::Wig => DensityFormat::Wig,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Level {
+    /// One promoter per gene
+    Gene,
+    /// One promoter per transcript (mRNA)
+    Transcript,
+}
+
+impl From<Level> for FeatureLevel {
+    fn from(value: Level) -> Self {
+        match value {
+            Level::Gene => FeatureLevel::Gene,
+            Level::Transcript => FeatureLevel::Transcript,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Coordinates {
+    /// 1-based closed, like GFF3
+    OneBased,
+    /// 0-based half-open, like BED
+    ZeroBased,
+}
+
+impl From<Coordinates> for CoordinateSystem {
+    fn from(value: Coordinates) -> Self {
+        match value {
+            Coordinates::OneBased => CoordinateSystem::OneBasedClosed,
+            Coordinates::ZeroBased => CoordinateSystem::ZeroBasedHalfOpen,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Ambiguity {
+    /// An ambiguous base only matches the identical pattern character
+    Strict,
+    /// An ambiguous base matches any overlapping IUPAC code
+    Permissive,
+    /// Skip the windows containing N
+    SkipN,
+}
+
+impl From<Ambiguity> for AmbiguityPolicy {
+    fn from(value: Ambiguity) -> Self {
+        match value {
+            Ambiguity::Strict => AmbiguityPolicy::Strict,
+            Ambiguity::Permissive => AmbiguityPolicy::Permissive,
+            Ambiguity::SkipN => AmbiguityPolicy::SkipN,
+        }
+    }
 }
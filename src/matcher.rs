@@ -0,0 +1,623 @@
+//!
+//! Pattern matchers compiled from the PLACE database.
+//!
+//! The matchers are built once (see `db`) and shared by every search,
+//! so a query only has to be scanned a single time per matcher:
+//!
+//! - `ExactAutomaton` for the elements made of A/C/G/T only,
+//! - `IupacMatcher` for the elements with IUPAC ambiguous bases.
+
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+
+use crate::error::Error;
+use crate::place_desc::SeqDesc;
+
+/// Strand of a compiled pattern, relative to the element in the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternStrand {
+    /// The element sequence as written in `place.seq`.
+    Forward,
+    /// The reverse complement of the element sequence.
+    Reverse,
+}
+
+/// How ambiguous IUPAC bases in the query are matched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AmbiguityPolicy {
+    /// An ambiguous query base only matches the identical pattern character.
+    #[default]
+    Strict,
+    /// An ambiguous query base matches when its IUPAC set overlaps the pattern's.
+    Permissive,
+    /// Like `Strict`, but windows containing `N` are never reported.
+    SkipN,
+}
+
+impl AmbiguityPolicy {
+    /// Whether a query byte satisfies a pattern character under the policy.
+    pub fn accepts(self, pattern_char: u8, query_char: u8) -> bool {
+        match self {
+            Self::Strict | Self::SkipN => iupac_accepts(pattern_char, query_char),
+            Self::Permissive => {
+                query_char == pattern_char || iupac_mask(query_char) & iupac_mask(pattern_char) != 0
+            }
+        }
+    }
+
+    /// Whether a hit covering `window` may be reported under the policy.
+    pub fn keeps(self, window: &[u8]) -> bool {
+        self != Self::SkipN || !window.contains(&b'N')
+    }
+}
+
+/// One occurrence of an element in the query.
+///
+/// `start` is the 0-based offset on the forward query sequence,
+/// whatever the strand of the pattern is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternHit {
+    pub element: usize,
+    pub start: usize,
+    pub strand: PatternStrand,
+    pub mismatches: usize,
+}
+
+/// An Aho-Corasick automaton over all exact elements
+/// and their reverse complements.
+#[derive(Debug, Clone)]
+pub struct ExactAutomaton {
+    automaton: AhoCorasick,
+    // pattern id -> (index into the element list, strand)
+    patterns: Vec<(usize, PatternStrand)>,
+}
+
+impl ExactAutomaton {
+    /// Build the automaton from the exact elements.
+    ///
+    /// The index of an element in `elements` is reported back in `PatternHit::element`.
+    pub fn new(elements: &[SeqDesc]) -> Result<Self, Error> {
+        let mut sequences = Vec::with_capacity(elements.len() * 2);
+        let mut patterns = Vec::with_capacity(elements.len() * 2);
+
+        for (i, desc) in elements.iter().enumerate() {
+            sequences.push(desc.sq.as_bytes().to_vec());
+            patterns.push((i, PatternStrand::Forward));
+
+            sequences.push(reverse_complement_bytes(desc.sq.as_bytes()));
+            patterns.push((i, PatternStrand::Reverse));
+        }
+
+        let automaton = AhoCorasickBuilder::new()
+            .match_kind(MatchKind::Standard)
+            .build(&sequences)
+            .map_err(|e| Error::DbBuild(e.to_string()))?;
+
+        Ok(Self {
+            automaton,
+            patterns,
+        })
+    }
+
+    /// Find every occurrence of every element on both strands, in one pass over `text`.
+    ///
+    /// A palindromic element is reported twice at the same offset, once per strand.
+    pub fn find_all(&self, text: &[u8]) -> Vec<PatternHit> {
+        self.automaton
+            .find_overlapping_iter(text)
+            .map(|m| {
+                let (element, strand) = self.patterns[m.pattern().as_usize()];
+                PatternHit {
+                    element,
+                    start: m.start(),
+                    strand,
+                    mismatches: 0,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Width of a shift-and state.
+/// Longer patterns can't be packed, they are matched one by one instead.
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A group of patterns packed side by side into one 64-bit shift-and state.
+#[derive(Debug, Clone)]
+struct PackedWord {
+    // byte of the query -> bits of the pattern positions it satisfies,
+    // for the strict and the permissive policy
+    strict: Box<[u64; 256]>,
+    permissive: Box<[u64; 256]>,
+    // the first bit of every pattern
+    init: u64,
+    // the last bit of every pattern
+    accept: u64,
+    // (last bit, pattern id) of every pattern
+    ends: Vec<(usize, usize)>,
+}
+
+impl PackedWord {
+    fn new() -> Self {
+        Self {
+            strict: Box::new([0; 256]),
+            permissive: Box::new([0; 256]),
+            init: 0,
+            accept: 0,
+            ends: Vec::new(),
+        }
+    }
+
+    fn table(&self, policy: AmbiguityPolicy) -> &[u64; 256] {
+        match policy {
+            AmbiguityPolicy::Permissive => &self.permissive,
+            AmbiguityPolicy::Strict | AmbiguityPolicy::SkipN => &self.strict,
+        }
+    }
+}
+
+/// A bit-parallel (shift-and) matcher over all IUPAC elements
+/// and their reverse complements.
+///
+/// Every pattern position is compiled into a 4-bit nucleotide mask,
+/// then the patterns are packed into 64-bit words so one query pass
+/// updates dozens of patterns at once.
+#[derive(Debug, Clone)]
+pub struct IupacMatcher {
+    words: Vec<PackedWord>,
+    // patterns which do not fit in a word: (pattern id, the pattern)
+    long: Vec<(usize, Vec<u8>)>,
+    // pattern id -> (index into the element list, strand, pattern length)
+    patterns: Vec<(usize, PatternStrand, usize)>,
+}
+
+impl IupacMatcher {
+    /// Build the matcher from the IUPAC elements.
+    ///
+    /// The index of an element in `elements` is reported back in `PatternHit::element`.
+    pub fn new(elements: &[SeqDesc]) -> Self {
+        let mut patterns = Vec::with_capacity(elements.len() * 2);
+        let mut sequences = Vec::with_capacity(elements.len() * 2);
+
+        for (i, desc) in elements.iter().enumerate() {
+            let len = desc.sq.len();
+            sequences.push(desc.sq.as_bytes().to_vec());
+            patterns.push((i, PatternStrand::Forward, len));
+
+            sequences.push(reverse_complement_bytes(desc.sq.as_bytes()));
+            patterns.push((i, PatternStrand::Reverse, len));
+        }
+
+        let mut words: Vec<PackedWord> = vec![];
+        let mut long = vec![];
+        let mut used = WORD_BITS;
+
+        for (id, seq) in sequences.iter().enumerate() {
+            let m = seq.len();
+            if m == 0 {
+                continue;
+            }
+            if m > WORD_BITS {
+                long.push((id, seq.clone()));
+                continue;
+            }
+
+            if used + m > WORD_BITS {
+                words.push(PackedWord::new());
+                used = 0;
+            }
+            let word = words.last_mut().unwrap();
+            for (j, &p) in seq.iter().enumerate() {
+                let bit = 1u64 << (used + j);
+                for c in 0..=u8::MAX {
+                    if AmbiguityPolicy::Strict.accepts(p, c) {
+                        word.strict[c as usize] |= bit;
+                    }
+                    if AmbiguityPolicy::Permissive.accepts(p, c) {
+                        word.permissive[c as usize] |= bit;
+                    }
+                }
+            }
+            word.init |= 1u64 << used;
+            word.accept |= 1u64 << (used + m - 1);
+            word.ends.push((used + m - 1, id));
+            used += m;
+        }
+
+        Self {
+            words,
+            long,
+            patterns,
+        }
+    }
+
+    /// Find every occurrence of every element on both strands, in one pass over `text`.
+    pub fn find_all(&self, text: &[u8]) -> Vec<PatternHit> {
+        self.find_within(text, 0, AmbiguityPolicy::Strict)
+    }
+
+    /// Find every occurrence of every element on both strands
+    /// with at most `max_mismatches` mismatched positions (Hamming distance).
+    ///
+    /// Each hit carries the smallest number of mismatches it was found with.
+    /// Ambiguous query bases are matched according to `policy`,
+    /// windows are not dropped here for `AmbiguityPolicy::SkipN`, see `AmbiguityPolicy::keeps`.
+    pub fn find_within(
+        &self,
+        text: &[u8],
+        max_mismatches: usize,
+        policy: AmbiguityPolicy,
+    ) -> Vec<PatternHit> {
+        let k = max_mismatches;
+        let mut hits = Vec::new();
+        // states[w * (k + 1) + d]: positions of word `w` matched with at most `d` mismatches
+        let mut states = vec![0u64; self.words.len() * (k + 1)];
+
+        for (i, &c) in text.iter().enumerate() {
+            for (w, word) in self.words.iter().enumerate() {
+                let state = &mut states[w * (k + 1)..(w + 1) * (k + 1)];
+                let mask = word.table(policy)[c as usize];
+
+                let mut prev_old = state[0];
+                state[0] = ((state[0] << 1) | word.init) & mask;
+                for d in state.iter_mut().skip(1) {
+                    let old = *d;
+                    // match the character, or substitute it with one more mismatch
+                    *d = (((old << 1) | word.init) & mask) | (prev_old << 1) | word.init;
+                    prev_old = old;
+                }
+
+                if state[k] & word.accept != 0 {
+                    for &(bit, id) in &word.ends {
+                        let bit = 1u64 << bit;
+                        if let Some(d) = state.iter().position(|s| s & bit != 0) {
+                            hits.push(self.hit(id, i, d));
+                        }
+                    }
+                }
+            }
+        }
+
+        for (id, pattern) in &self.long {
+            let m = pattern.len();
+            if m > text.len() {
+                continue;
+            }
+            for start in 0..=text.len() - m {
+                let mut mismatches = 0;
+                for (&p, &c) in pattern.iter().zip(&text[start..start + m]) {
+                    if !policy.accepts(p, c) {
+                        mismatches += 1;
+                        if mismatches > k {
+                            break;
+                        }
+                    }
+                }
+                if mismatches <= k {
+                    hits.push(self.hit(*id, start + m - 1, mismatches));
+                }
+            }
+        }
+
+        hits
+    }
+
+    /// Turn a pattern ending at `last` into a hit.
+    fn hit(&self, id: usize, last: usize, mismatches: usize) -> PatternHit {
+        let (element, strand, len) = self.patterns[id];
+        PatternHit {
+            element,
+            start: last + 1 - len,
+            strand,
+            mismatches,
+        }
+    }
+}
+
+/// Whether a query byte satisfies a pattern character.
+///
+/// A query base matches when it's the same character,
+/// or when it's a concrete base (A/C/G/T) inside the IUPAC set of the pattern.
+pub fn iupac_accepts(pattern_char: u8, query_char: u8) -> bool {
+    let concrete = matches!(query_char, b'A' | b'C' | b'G' | b'T');
+    query_char == pattern_char
+        || (concrete && iupac_mask(query_char) & iupac_mask(pattern_char) != 0)
+}
+
+/// The 0-based offsets in `text` where the hit of `element` doesn't match under `policy`.
+pub fn mismatch_positions(
+    text: &[u8],
+    hit: &PatternHit,
+    element: &[u8],
+    policy: AmbiguityPolicy,
+) -> Vec<usize> {
+    let pattern = match hit.strand {
+        PatternStrand::Forward => element.to_vec(),
+        PatternStrand::Reverse => reverse_complement_bytes(element),
+    };
+    let window = &text[hit.start..hit.start + pattern.len()];
+
+    pattern
+        .iter()
+        .zip(window)
+        .enumerate()
+        .filter(|(_, (p, q))| !policy.accepts(**p, **q))
+        .map(|(j, _)| hit.start + j)
+        .collect()
+}
+
+/// The 4-bit nucleotide mask of an IUPAC code: A=1, C=2, G=4, T=8.
+///
+/// Unknown characters have an empty mask.
+pub fn iupac_mask(c: u8) -> u8 {
+    match c {
+        b'A' => 0b0001,
+        b'C' => 0b0010,
+        b'G' => 0b0100,
+        b'T' => 0b1000,
+        b'R' => 0b0101,
+        b'Y' => 0b1010,
+        b'M' => 0b0011,
+        b'K' => 0b1100,
+        b'S' => 0b0110,
+        b'W' => 0b1001,
+        b'B' => 0b1110,
+        b'D' => 0b1101,
+        b'H' => 0b1011,
+        b'V' => 0b0111,
+        b'N' => 0b1111,
+        _ => 0,
+    }
+}
+
+/// Complement of a base over the full IUPAC alphabet.
+///
+/// `S`, `W`, `N` and unknown characters are their own complement.
+pub fn complement(c: u8) -> u8 {
+    match c {
+        b'A' => b'T',
+        b'T' => b'A',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'M' => b'K',
+        b'K' => b'M',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        _ => c,
+    }
+}
+
+/// Get reverse complement of a byte sequence, over the full IUPAC alphabet.
+pub fn reverse_complement_bytes(seq: &[u8]) -> Vec<u8> {
+    seq.iter().rev().map(|&c| complement(c)).collect()
+}
+
+/// Get reverse complement chain, over the full IUPAC alphabet.
+///
+/// Non-ASCII characters are kept as they are, see `reverse_complement_bytes` for bytes.
+pub fn reverse_complement(query: &str) -> String {
+    query
+        .chars()
+        .rev()
+        .map(|c| {
+            if c.is_ascii() {
+                complement(c as u8) as char
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(sq: &str) -> SeqDesc {
+        SeqDesc {
+            id: sq.to_string(),
+            ac: String::new(),
+            dt: String::new(),
+            de: String::new(),
+            kw: vec![],
+            os: String::new(),
+            refs: vec![],
+            sq: sq.to_string(),
+        }
+    }
+
+    /// A reproducible sequence over `alphabet`.
+    fn random_seq(len: usize, alphabet: &[u8], mut seed: u64) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                alphabet[((seed >> 33) % alphabet.len() as u64) as usize]
+            })
+            .collect()
+    }
+
+    /// Every pattern, on both strands, compared with every window of `text`.
+    fn naive_hits(
+        elements: &[SeqDesc],
+        text: &[u8],
+        k: usize,
+        policy: AmbiguityPolicy,
+    ) -> Vec<(usize, usize, bool, usize)> {
+        let mut hits = vec![];
+        for (i, desc) in elements.iter().enumerate() {
+            let forward = desc.sq.as_bytes().to_vec();
+            let reverse = reverse_complement_bytes(&forward);
+            for (pattern, is_forward) in [(forward, true), (reverse, false)] {
+                let m = pattern.len();
+                for start in 0..(text.len() + 1).saturating_sub(m) {
+                    let window = &text[start..start + m];
+                    let mismatches = pattern
+                        .iter()
+                        .zip(window)
+                        .filter(|(p, q)| !policy.accepts(**p, **q))
+                        .count();
+                    if mismatches <= k && policy.keeps(window) {
+                        hits.push((i, start, is_forward, mismatches));
+                    }
+                }
+            }
+        }
+        hits.sort_unstable();
+        hits
+    }
+
+    fn matcher_hits(
+        elements: &[SeqDesc],
+        matcher: &IupacMatcher,
+        text: &[u8],
+        k: usize,
+        policy: AmbiguityPolicy,
+    ) -> Vec<(usize, usize, bool, usize)> {
+        let mut hits: Vec<_> = matcher
+            .find_within(text, k, policy)
+            .into_iter()
+            .filter(|hit| {
+                let len = elements[hit.element].sq.len();
+                policy.keeps(&text[hit.start..hit.start + len])
+            })
+            .map(|hit| {
+                let forward = hit.strand == PatternStrand::Forward;
+                (hit.element, hit.start, forward, hit.mismatches)
+            })
+            .collect();
+        hits.sort_unstable();
+        hits
+    }
+
+    fn elements() -> Vec<SeqDesc> {
+        let mut sequences: Vec<String> = vec![
+            "CNGTTR".to_string(),
+            "WAACCA".to_string(),
+            "TGAC".to_string(),
+            "YTCANTYY".to_string(),
+            "RCCGACNT".to_string(),
+        ];
+        // Enough patterns to fill several words
+        for i in 0..12 {
+            let seq = random_seq(9 + i % 5, b"ACGTRYSWN", 100 + i as u64);
+            sequences.push(String::from_utf8(seq).unwrap());
+        }
+        // Longer than a word, matched one by one
+        sequences.push(String::from_utf8(random_seq(70, b"ACGTRYN", 7)).unwrap());
+        sequences.push(String::from_utf8(random_seq(100, b"ACGT", 8)).unwrap());
+        sequences.iter().map(|sq| element(sq)).collect()
+    }
+
+    fn text(elements: &[SeqDesc]) -> Vec<u8> {
+        // Ambiguous query bases, and both long patterns planted on opposite strands
+        let mut text = random_seq(1500, b"ACGTACGTACGTNRY", 1);
+        let long = elements[elements.len() - 1].sq.as_bytes();
+        text[200..200 + long.len()].copy_from_slice(long);
+        let long = reverse_complement_bytes(
+            &elements[elements.len() - 2]
+                .sq
+                .bytes()
+                .map(|c| match c {
+                    b'R' => b'G',
+                    b'Y' => b'C',
+                    b'N' => b'A',
+                    c => c,
+                })
+                .collect::<Vec<u8>>(),
+        );
+        text[900..900 + long.len()].copy_from_slice(&long);
+        text
+    }
+
+    #[test]
+    fn iupac_matcher_matches_a_naive_scan() {
+        let elements = elements();
+        let matcher = IupacMatcher::new(&elements);
+        assert!(matcher.words.len() > 1);
+        assert_eq!(matcher.long.len(), 4);
+
+        let text = text(&elements);
+        for policy in [
+            AmbiguityPolicy::Strict,
+            AmbiguityPolicy::Permissive,
+            AmbiguityPolicy::SkipN,
+        ] {
+            for k in 0..=2 {
+                let expected = naive_hits(&elements, &text, k, policy);
+                assert!(expected.iter().any(|hit| !hit.2), "{:?} {}", policy, k);
+                assert_eq!(
+                    matcher_hits(&elements, &matcher, &text, k, policy),
+                    expected,
+                    "{:?} with {} mismatches",
+                    policy,
+                    k
+                );
+            }
+        }
+        assert_eq!(
+            matcher.find_all(&text).len(),
+            naive_hits(&elements, &text, 0, AmbiguityPolicy::Strict).len()
+        );
+    }
+
+    #[test]
+    fn long_patterns_are_found_on_both_strands() {
+        let elements = elements();
+        let matcher = IupacMatcher::new(&elements);
+        let text = text(&elements);
+        let long: Vec<_> = matcher
+            .find_all(&text)
+            .into_iter()
+            .filter(|hit| hit.element >= elements.len() - 2)
+            .collect();
+        assert!(long.contains(&PatternHit {
+            element: elements.len() - 1,
+            start: 200,
+            strand: PatternStrand::Forward,
+            mismatches: 0,
+        }));
+        assert!(long.contains(&PatternHit {
+            element: elements.len() - 2,
+            start: 900,
+            strand: PatternStrand::Reverse,
+            mismatches: 0,
+        }));
+    }
+
+    #[test]
+    fn ambiguous_query_bases() {
+        let matcher = IupacMatcher::new(&[element("ACNT")]);
+        let hits = |text: &[u8], policy| -> Vec<(usize, PatternStrand)> {
+            matcher
+                .find_within(text, 0, policy)
+                .into_iter()
+                .filter(|hit| policy.keeps(&text[hit.start..hit.start + 4]))
+                .map(|hit| (hit.start, hit.strand))
+                .collect()
+        };
+
+        // An `N` in the query only matches the `N` of the pattern when strict
+        assert_eq!(
+            hits(b"ACNT", AmbiguityPolicy::Strict),
+            [(0, PatternStrand::Forward)]
+        );
+        assert!(hits(b"ACNT", AmbiguityPolicy::SkipN).is_empty());
+        // `R` (A/G) doesn't overlap `C`, but `M` (A/C) does
+        assert!(hits(b"ARAT", AmbiguityPolicy::Permissive).is_empty());
+        assert_eq!(
+            hits(b"AMAT", AmbiguityPolicy::Permissive),
+            [(0, PatternStrand::Forward)]
+        );
+        assert!(hits(b"AMAT", AmbiguityPolicy::Strict).is_empty());
+        // `ANNT` is both strands of `ACNT` (reverse `ANGT`) when permissive
+        assert_eq!(
+            hits(b"ANNT", AmbiguityPolicy::Permissive),
+            [(0, PatternStrand::Forward), (0, PatternStrand::Reverse)]
+        );
+    }
+}
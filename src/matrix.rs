@@ -0,0 +1,225 @@
+//!
+//! Query-by-element matrices of the hits, for clustering and co-expression tools.
+//!
+//! Each row is a query, each column an element or a category of elements,
+//! and each cell holds one of the `MatrixValue`s:
+//!
+//! - `Count`: the hits of the column in the query,
+//! - `Presence`: 1 if the query has a hit of the column, 0 otherwise,
+//! - `Strand`: the hits on each strand, in two columns `ID(+)` and `ID(-)`.
+//!
+//! The categories are the PLACE keywords (`KW`) of the elements,
+//! an element with several keywords counts in each of them and one without any in none.
+//!
+//! The matrix is written as a dense TSV table, or as a sparse Matrix Market file
+//! whose rows and columns are named in two other files, one name per line.
+
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+
+use crate::coords::Strand;
+use crate::db::Database;
+use crate::io::{SearchResult, SearchedDesc};
+
+/// What a cell of the matrix holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatrixValue {
+    #[default]
+    Count,
+    Presence,
+    Strand,
+}
+
+/// What the columns of the matrix are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatrixColumns {
+    /// One column per element ID.
+    #[default]
+    Elements,
+    /// One column per PLACE keyword.
+    Keywords,
+}
+
+/// A sparse query-by-element matrix.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HitMatrix {
+    /// The query IDs, in the order of the results.
+    pub rows: Vec<String>,
+    /// The column names, sorted.
+    pub columns: Vec<String>,
+    /// The non-zero cells, `(row, column, value)`, by row then column.
+    pub cells: Vec<(usize, usize, usize)>,
+}
+
+impl HitMatrix {
+    /// Build the matrix of the results, one row per result even without hits.
+    ///
+    /// Only the columns with a hit are kept, unless `all_columns`
+    /// asks for every element or keyword of the database.
+    pub fn from_results(
+        db: &Database,
+        results: &[SearchResult],
+        columns: MatrixColumns,
+        value: MatrixValue,
+        all_columns: bool,
+    ) -> Self {
+        let keywords: HashMap<&str, &[String]> = match columns {
+            MatrixColumns::Elements => HashMap::new(),
+            MatrixColumns::Keywords => db
+                .place_db()
+                .seq_desc
+                .all
+                .iter()
+                .map(|desc| (desc.id.as_str(), desc.kw.as_slice()))
+                .collect(),
+        };
+        let names = |desc: &SearchedDesc| -> Vec<String> {
+            let bases: Vec<&str> = match columns {
+                MatrixColumns::Elements => vec![desc.e_id],
+                MatrixColumns::Keywords => keywords
+                    .get(desc.e_id)
+                    .map(|kw| kw.iter().map(|k| k.as_str()).collect())
+                    .unwrap_or_default(),
+            };
+            bases
+                .into_iter()
+                .map(|base| column_name(base, value, desc.q_strand))
+                .collect()
+        };
+
+        let mut counts: Vec<HashMap<String, usize>> = Vec::with_capacity(results.len());
+        let mut column_set: BTreeSet<String> = BTreeSet::new();
+        for result in results {
+            let mut row: HashMap<String, usize> = HashMap::new();
+            for desc in &result.search_descs {
+                for name in names(desc) {
+                    *row.entry(name).or_insert(0) += 1;
+                }
+            }
+            column_set.extend(row.keys().cloned());
+            counts.push(row);
+        }
+        if all_columns {
+            column_set.extend(all_column_names(db, columns, value));
+        }
+
+        let columns: Vec<String> = column_set.into_iter().collect();
+        let index: HashMap<&str, usize> = columns
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect();
+        let mut cells = vec![];
+        for (row, found) in counts.iter().enumerate() {
+            let mut row_cells: Vec<(usize, usize, usize)> = found
+                .iter()
+                .map(|(name, &count)| {
+                    let count = match value {
+                        MatrixValue::Presence => 1,
+                        MatrixValue::Count | MatrixValue::Strand => count,
+                    };
+                    (row, index[name.as_str()], count)
+                })
+                .collect();
+            row_cells.sort_unstable();
+            cells.extend(row_cells);
+        }
+
+        Self {
+            rows: results.iter().map(|result| result.id.clone()).collect(),
+            columns,
+            cells,
+        }
+    }
+
+    /// The value of a cell, 0 if it isn't stored.
+    pub fn get(&self, row: usize, column: usize) -> usize {
+        self.cells
+            .binary_search_by(|&(r, c, _)| (r, c).cmp(&(row, column)))
+            .map(|i| self.cells[i].2)
+            .unwrap_or(0)
+    }
+
+    /// Write the dense matrix as a TSV table, with a header line of the column names.
+    pub fn write_tsv<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        write!(writer, "Query ID")?;
+        for column in &self.columns {
+            write!(writer, "\t{}", column)?;
+        }
+        writeln!(writer)?;
+
+        let mut cells = self.cells.iter().peekable();
+        for (row, id) in self.rows.iter().enumerate() {
+            let mut values = vec![0; self.columns.len()];
+            while let Some(&&(r, c, value)) = cells.peek() {
+                if r != row {
+                    break;
+                }
+                values[c] = value;
+                cells.next();
+            }
+            write!(writer, "{}", id)?;
+            for value in values {
+                write!(writer, "\t{}", value)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Write the non-zero cells in the Matrix Market coordinate format, 1-based.
+    pub fn write_matrix_market<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, "%%MatrixMarket matrix coordinate integer general")?;
+        writeln!(
+            writer,
+            "{} {} {}",
+            self.rows.len(),
+            self.columns.len(),
+            self.cells.len()
+        )?;
+        for &(row, column, value) in &self.cells {
+            writeln!(writer, "{} {} {}", row + 1, column + 1, value)?;
+        }
+        Ok(())
+    }
+
+    /// Write names, one per line, for the rows or the columns of a Matrix Market file.
+    pub fn write_names<W: Write>(names: &[String], mut writer: W) -> std::io::Result<()> {
+        for name in names {
+            writeln!(writer, "{}", name)?;
+        }
+        Ok(())
+    }
+}
+
+/// The column of an element or a keyword, by strand for `MatrixValue::Strand`.
+fn column_name(base: &str, value: MatrixValue, strand: Strand) -> String {
+    match value {
+        MatrixValue::Strand => format!("{}({})", base, strand),
+        MatrixValue::Count | MatrixValue::Presence => base.to_string(),
+    }
+}
+
+/// The columns of every element or keyword of the database.
+fn all_column_names(db: &Database, columns: MatrixColumns, value: MatrixValue) -> Vec<String> {
+    let bases: BTreeSet<&str> = db
+        .place_db()
+        .seq_desc
+        .all
+        .iter()
+        .flat_map(|desc| match columns {
+            MatrixColumns::Elements => vec![desc.id.as_str()],
+            MatrixColumns::Keywords => desc.kw.iter().map(|k| k.as_str()).collect(),
+        })
+        .collect();
+    bases
+        .into_iter()
+        .flat_map(|base| match value {
+            MatrixValue::Strand => vec![
+                column_name(base, value, Strand::Plus),
+                column_name(base, value, Strand::Minus),
+            ],
+            MatrixValue::Count | MatrixValue::Presence => vec![base.to_string()],
+        })
+        .collect()
+}
@@ -0,0 +1,201 @@
+//!
+//! Writers of the search hits in standard formats,
+//! to load them in genome browsers like IGV or JBrowse.
+//!
+//! - `Tsv`: the columns of `SearchedDesc::row`, with a header line,
+//! - `Bed`: BED6, the element ID as name,
+//! - `Gff3`: GFF3, the element ID, accession and description as attributes,
+//! - `Json`: a JSON array of `OwnedSearchedDesc`,
+//! - `JsonLines`: one `OwnedSearchedDesc` JSON object per line.
+//!
+//! Each format declares its coordinate system with `OutputFormat::coordinate_system`:
+//! BED is always 0-based half-open and GFF3 always 1-based closed,
+//! TSV and JSON are 1-based closed unless `HitWriter::with_coordinates` says otherwise.
+
+use std::io::Write;
+
+use crate::coords::CoordinateSystem;
+use crate::db::Database;
+use crate::io::{SearchResult, SearchedDesc};
+
+/// The source column of the GFF3 lines.
+const GFF3_SOURCE: &str = "placecare";
+
+/// The type column of the GFF3 lines, `SO:0001055`.
+const GFF3_TYPE: &str = "transcriptional_cis_regulatory_region";
+
+/// The formats the hits can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Tsv,
+    Bed,
+    Gff3,
+    Json,
+    JsonLines,
+}
+
+impl OutputFormat {
+    /// The coordinate system the format is written in by default.
+    pub fn coordinate_system(self) -> CoordinateSystem {
+        match self {
+            OutputFormat::Bed => CoordinateSystem::ZeroBasedHalfOpen,
+            OutputFormat::Gff3
+            | OutputFormat::Tsv
+            | OutputFormat::Json
+            | OutputFormat::JsonLines => CoordinateSystem::OneBasedClosed,
+        }
+    }
+
+    /// Whether the coordinate system of the format is fixed by its specification.
+    pub fn has_fixed_coordinates(self) -> bool {
+        matches!(self, OutputFormat::Bed | OutputFormat::Gff3)
+    }
+}
+
+/// A writer of hits, one line per hit.
+///
+/// The header of the format, if any, is written on creation,
+/// `finish` must be called at the end to close a JSON array.
+pub struct HitWriter<'a, W: Write> {
+    writer: W,
+    format: OutputFormat,
+    coordinates: CoordinateSystem,
+    // the database to take the element keywords and organism from, for the JSON formats
+    metadata: Option<&'a Database>,
+    written: usize,
+}
+
+impl<'a, W: Write> HitWriter<'a, W> {
+    pub fn new(mut writer: W, format: OutputFormat) -> std::io::Result<Self> {
+        match format {
+            OutputFormat::Tsv => write!(writer, "{}", SearchedDesc::header())?,
+            OutputFormat::Gff3 => writeln!(writer, "##gff-version 3")?,
+            OutputFormat::Json => write!(writer, "[")?,
+            OutputFormat::Bed | OutputFormat::JsonLines => {}
+        }
+        Ok(Self {
+            writer,
+            format,
+            coordinates: format.coordinate_system(),
+            metadata: None,
+            written: 0,
+        })
+    }
+
+    /// Add the keywords and the organism of the elements, from `db`, to the JSON hits.
+    pub fn with_metadata(mut self, db: &'a Database) -> Self {
+        self.metadata = Some(db);
+        self
+    }
+
+    /// Write the TSV and JSON positions in `system`.
+    ///
+    /// BED and GFF3 keep the system of their specification.
+    pub fn with_coordinates(mut self, system: CoordinateSystem) -> Self {
+        if !self.format.has_fixed_coordinates() {
+            self.coordinates = system;
+        }
+        self
+    }
+
+    /// The coordinate system the hits are written in.
+    pub fn coordinates(&self) -> CoordinateSystem {
+        self.coordinates
+    }
+
+    /// Write one hit.
+    pub fn write(&mut self, hit: &SearchedDesc) -> std::io::Result<()> {
+        self.written += 1;
+        let w = &mut self.writer;
+        let (start, end) = hit.q_coords.in_system(self.coordinates);
+
+        match self.format {
+            OutputFormat::Tsv => write!(w, "{}", hit.row(self.coordinates)),
+            OutputFormat::Bed => writeln!(
+                w,
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                hit.q_id, start, end, hit.e_id, 0, hit.q_strand
+            ),
+            OutputFormat::Gff3 => {
+                let mut attributes = vec![
+                    format!("Name={}", gff3_escape(hit.e_id)),
+                    format!("Dbxref=PLACE:{}", gff3_escape(hit.e_ac)),
+                    format!("Note={}", gff3_escape(hit.e_desc)),
+                    format!("mismatches={}", hit.mismatches),
+                ];
+                if !hit.mismatch_pos.is_empty() {
+                    let positions: Vec<String> = hit
+                        .mismatch_pos
+                        .iter()
+                        .map(|&p| self.coordinates.position(p).to_string())
+                        .collect();
+                    attributes.push(format!("mismatch_positions={}", positions.join(",")));
+                }
+                if let Some((rel_start, rel_end)) = hit.relative() {
+                    attributes.push(format!("relative_start={}", rel_start));
+                    attributes.push(format!("relative_end={}", rel_end));
+                }
+                writeln!(
+                    w,
+                    "{}\t{}\t{}\t{}\t{}\t.\t{}\t.\t{}",
+                    gff3_escape(hit.q_id),
+                    GFF3_SOURCE,
+                    GFF3_TYPE,
+                    start,
+                    end,
+                    hit.q_strand,
+                    attributes.join(";")
+                )
+            }
+            OutputFormat::Json | OutputFormat::JsonLines => {
+                let mut owned = hit.to_owned_desc(self.coordinates);
+                if let Some(db) = self.metadata {
+                    owned = owned.with_metadata(db);
+                }
+                if self.format == OutputFormat::Json {
+                    writeln!(w, "{}", if self.written > 1 { "," } else { "" })?;
+                }
+                serde_json::to_writer(&mut *w, &owned)?;
+                if self.format == OutputFormat::JsonLines {
+                    writeln!(w)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Write all the hits of the results, in order.
+    pub fn write_all(&mut self, results: &[SearchResult]) -> std::io::Result<()> {
+        for result in results {
+            for hit in &result.search_descs {
+                self.write(hit)?;
+            }
+        }
+        self.writer.flush()
+    }
+
+    /// Write the end of the format, flush and get back the underlying writer.
+    pub fn finish(mut self) -> std::io::Result<W> {
+        if self.format == OutputFormat::Json {
+            writeln!(self.writer, "{}]", if self.written > 0 { "\n" } else { "" })?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Percent-encode the characters GFF3 reserves in columns and attribute values.
+fn gff3_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            ';' | '=' | '&' | ',' | '%' | '\t' | '\n' | '\r' => {
+                escaped.push_str(&format!("%{:02X}", c as u32))
+            }
+            c if c.is_control() => escaped.push_str(&format!("%{:02X}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
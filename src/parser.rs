@@ -0,0 +1,394 @@
+//!
+//! Reader and writer of the PLACE flat file format (`place.seq`).
+//!
+//! An entry looks like:
+//!
+//! ```text
+//! ID   TATABOX1
+//! XX
+//! AC   S000108
+//! XX
+//! DE   "TATA box"; TATA box found in the 5'upstream region of rice
+//! DE   alpha-amylase;
+//! XX
+//! KW   TATA box; promoter;
+//! XX
+//! SQ
+//!      CTATAAATAC
+//! //
+//! ```
+//!
+//! Continuation lines of a field are joined with a single space,
+//! keywords are split on `;` after the lines are joined.
+
+use std::fmt;
+use std::io::{BufRead, Write};
+
+use crate::place_desc::{Reference, SeqDesc};
+
+/// Width of the lines written by `PlaceSeqWriter`, the tag included.
+const LINE_WIDTH: usize = 72;
+
+/// The error returned while reading a `place.seq` file.
+#[derive(Debug)]
+pub enum ParseError {
+    /// The underlying reader failed.
+    Io(std::io::Error),
+    /// The content is not a valid PLACE entry, at a 1-based line number.
+    Format { line: usize, message: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "Failed to read place.seq: {}", e),
+            ParseError::Format { line, message } => {
+                write!(f, "Invalid place.seq at line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            ParseError::Format { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ParseError {
+    fn from(e: std::io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
+/// A streaming reader of `place.seq` entries.
+///
+/// Entries are read one at a time from any `BufRead`,
+/// the iteration stops after the first error.
+///
+/// ```rust
+/// use placecare::parser::PlaceSeqReader;
+///
+/// let text = "ID   GATABOX\nXX\nAC   S000039\nXX\nSQ\n     GATA\n//\n";
+/// let descs: Vec<_> = PlaceSeqReader::new(text.as_bytes())
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(descs[0].sq, "GATA");
+/// ```
+pub struct PlaceSeqReader<R> {
+    reader: R,
+    line_no: usize,
+    buf: String,
+    done: bool,
+}
+
+impl<R: BufRead> PlaceSeqReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line_no: 0,
+            buf: String::new(),
+            done: false,
+        }
+    }
+
+    /// Read the next entry, `None` at the end of the input.
+    fn read_entry(&mut self) -> Result<Option<SeqDesc>, ParseError> {
+        let mut entry = EntryBuilder::default();
+
+        loop {
+            self.buf.clear();
+            if self.reader.read_line(&mut self.buf)? == 0 {
+                // A last entry without the closing `//` is still accepted
+                return if entry.is_empty() {
+                    Ok(None)
+                } else {
+                    entry.finish(self.line_no).map(Some)
+                };
+            }
+            self.line_no += 1;
+
+            let line = self.buf.trim_end();
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line.trim() == "//" {
+                if entry.is_empty() {
+                    continue;
+                }
+                return entry.finish(self.line_no).map(Some);
+            }
+            entry.push_line(line, self.line_no)?;
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PlaceSeqReader<R> {
+    type Item = Result<SeqDesc, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.read_entry() {
+            Ok(Some(desc)) => Some(Ok(desc)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Parse all the entries of a `place.seq` content.
+pub fn parse_place_seq(text: &str) -> Result<Vec<SeqDesc>, ParseError> {
+    PlaceSeqReader::new(text.as_bytes()).collect()
+}
+
+/// The fields of an entry while its lines are read.
+#[derive(Default)]
+struct EntryBuilder {
+    id: Option<String>,
+    ac: Vec<String>,
+    dt: Vec<String>,
+    de: Vec<String>,
+    kw: Vec<String>,
+    os: Vec<String>,
+    refs: Vec<ReferenceBuilder>,
+    // whether the reference lines go to the last reference,
+    // a new one starts after any other line (XX included)
+    in_ref: bool,
+    sq: Option<String>,
+}
+
+/// The lines of a reference block while they are read.
+#[derive(Default)]
+struct ReferenceBuilder {
+    ra: Vec<String>,
+    rt: Vec<String>,
+    rl: Vec<String>,
+    rd: Vec<String>,
+    rc: Vec<String>,
+}
+
+impl ReferenceBuilder {
+    fn finish(self) -> Reference {
+        let mut reference = Reference {
+            authors: self.ra.join(" "),
+            title: self.rt.join(" "),
+            location: self.rl.join(" "),
+            comments: self.rc.join(" "),
+            ..Default::default()
+        };
+        reference.set_details(&self.rd.join(" "));
+        reference
+    }
+}
+
+impl EntryBuilder {
+    fn is_empty(&self) -> bool {
+        self.id.is_none()
+    }
+
+    fn push_line(&mut self, line: &str, line_no: usize) -> Result<(), ParseError> {
+        let error = |message: String| ParseError::Format {
+            line: line_no,
+            message,
+        };
+
+        // The sequence lines follow `SQ` and are indented
+        if let Some(sq) = &mut self.sq
+            && line.starts_with(char::is_whitespace)
+        {
+            sq.push_str(line.trim());
+            return Ok(());
+        }
+
+        let (tag, value) = match line.get(..2) {
+            Some(tag) => (tag, line[2..].trim()),
+            None => return Err(error(format!("Unknown line: {:?}", line))),
+        };
+
+        if self.id.is_none() && tag != "ID" {
+            return Err(error(format!("Expected an ID line, found {:?}", line)));
+        }
+
+        let is_ref = matches!(tag, "RA" | "RT" | "RL" | "RD" | "RC");
+        if is_ref && !self.in_ref {
+            self.refs.push(ReferenceBuilder::default());
+        }
+        self.in_ref = is_ref;
+
+        match tag {
+            "ID" => {
+                if self.id.is_some() {
+                    return Err(error("Entry has more than one ID line".to_string()));
+                }
+                self.id = Some(value.to_string());
+            }
+            "XX" => {}
+            "AC" => self.ac.push(value.to_string()),
+            "DT" => self.dt.push(value.to_string()),
+            "DE" => self.de.push(value.to_string()),
+            "KW" => self.kw.push(value.to_string()),
+            "OS" => self.os.push(value.to_string()),
+            "RA" | "RT" | "RL" | "RD" | "RC" => {
+                let reference = self.refs.last_mut().expect("A reference was just opened");
+                let lines = match tag {
+                    "RA" => &mut reference.ra,
+                    "RT" => &mut reference.rt,
+                    "RL" => &mut reference.rl,
+                    "RD" => &mut reference.rd,
+                    _ => &mut reference.rc,
+                };
+                lines.push(value.to_string());
+            }
+            "SQ" => self.sq = Some(value.to_string()),
+            _ => return Err(error(format!("Unknown line: {:?}", line))),
+        }
+        Ok(())
+    }
+
+    fn finish(self, line_no: usize) -> Result<SeqDesc, ParseError> {
+        let id = self.id.unwrap_or_default();
+        let sq = match self.sq {
+            Some(sq) if !sq.is_empty() => sq,
+            _ => {
+                return Err(ParseError::Format {
+                    line: line_no,
+                    message: format!("Entry {} has no sequence", id),
+                });
+            }
+        };
+
+        Ok(SeqDesc {
+            id,
+            ac: self.ac.join(" "),
+            dt: self.dt.join(" "),
+            de: self.de.join(" "),
+            kw: self
+                .kw
+                .join(" ")
+                .split(';')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            os: self.os.join(" "),
+            refs: self
+                .refs
+                .into_iter()
+                .map(ReferenceBuilder::finish)
+                .collect(),
+            sq,
+        })
+    }
+}
+
+/// A writer of `place.seq` entries.
+///
+/// Long fields are wrapped on spaces,
+/// so reading the output back gives the same `SeqDesc`s.
+pub struct PlaceSeqWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> PlaceSeqWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Write one entry, terminated by `//`.
+    pub fn write(&mut self, desc: &SeqDesc) -> std::io::Result<()> {
+        let w = &mut self.writer;
+        writeln!(w, "ID   {}", desc.id)?;
+
+        let keywords: String = desc.kw.iter().map(|k| format!("{}; ", k)).collect();
+        let mut blocks: Vec<Vec<(&str, &str)>> = vec![
+            vec![("AC", &desc.ac)],
+            vec![("DT", &desc.dt)],
+            vec![("DE", &desc.de)],
+            vec![("KW", keywords.trim_end())],
+            vec![("OS", &desc.os)],
+        ];
+        for reference in &desc.refs {
+            blocks.push(vec![
+                ("RA", &reference.authors),
+                ("RT", &reference.title),
+                ("RL", &reference.location),
+                ("RD", &reference.details),
+                ("RC", &reference.comments),
+            ]);
+        }
+
+        for block in blocks {
+            if block.iter().all(|(_, value)| value.is_empty()) {
+                continue;
+            }
+            writeln!(w, "XX")?;
+            for (tag, value) in block {
+                for line in wrap(value, LINE_WIDTH - 5) {
+                    writeln!(w, "{}   {}", tag, line)?;
+                }
+            }
+        }
+
+        writeln!(w, "XX")?;
+        writeln!(w, "SQ")?;
+        writeln!(w, "     {}", desc.sq)?;
+        writeln!(w, "//")
+    }
+
+    /// Write all the entries.
+    pub fn write_all<'a, I>(&mut self, descs: I) -> std::io::Result<()>
+    where
+        I: IntoIterator<Item = &'a SeqDesc>,
+    {
+        for desc in descs {
+            self.write(desc)?;
+        }
+        self.writer.flush()
+    }
+
+    /// Get back the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Split `text` into lines of at most `width` bytes where possible.
+///
+/// Lines are only broken on a single space, which is the one the reader puts back.
+fn wrap(text: &str, width: usize) -> Vec<&str> {
+    let mut lines = vec![];
+    let mut rest = text;
+
+    while rest.len() > width {
+        let bytes = rest.as_bytes();
+        let breakable = |i: usize| {
+            bytes[i] == b' ' && i > 0 && bytes[i - 1] != b' ' && bytes.get(i + 1) != Some(&b' ')
+        };
+        let at = (1..=width.min(bytes.len() - 1))
+            .rev()
+            .find(|&i| breakable(i))
+            .or_else(|| (width + 1..bytes.len()).find(|&i| breakable(i)));
+
+        match at {
+            Some(at) => {
+                lines.push(&rest[..at]);
+                rest = &rest[at + 1..];
+            }
+            None => break,
+        }
+    }
+    if !rest.is_empty() {
+        lines.push(rest);
+    }
+    lines
+}
@@ -1,10 +1,12 @@
 //!
 //! This module contains a structure of the PLACE database.
 
-use bincode::Decode;
+use bincode::{Decode, Encode};
 use std::collections::HashMap;
 
-#[derive(Decode, Debug, Clone)]
+use crate::parser::{ParseError, parse_place_seq};
+
+#[derive(Encode, Decode, Debug, Clone)]
 pub struct PlaceDB {
     pub seq_desc: SeqBuilder,
     pub seq_index: PlaceIndex,
@@ -17,46 +19,172 @@ impl PlaceDB {
             seq_index: index,
         }
     }
+
+    /// Build the database from the content of a `place.seq` flat file.
+    pub fn from_place_seq(seq: &str) -> Result<PlaceDB, ParseError> {
+        Ok(PlaceDB::from_descs(parse_place_seq(seq)?))
+    }
+
+    /// Build the database from parsed elements.
+    pub fn from_descs(seq_descs: Vec<SeqDesc>) -> PlaceDB {
+        let place_index = PlaceIndex::from_descs(&seq_descs);
+        let place_seq = SeqBuilder::from_seq_builder(seq_descs);
+        PlaceDB::new(place_seq, place_index)
+    }
 }
 
 /// The structure split into 2 parts.
-#[derive(Decode, Debug, Clone)]
+#[derive(Encode, Decode, Debug, Clone)]
 pub struct SeqBuilder {
     pub exact: Vec<SeqDesc>,
     pub iupac: Vec<SeqDesc>,
     pub all: Vec<SeqDesc>,
 }
 
+impl SeqBuilder {
+    /// Split the elements into exact (A/C/G/T only) and IUPAC ones.
+    ///
+    /// Elements which are neither (e.g. with `U`) are only kept in `all`.
+    pub fn from_seq_builder(seq: Vec<SeqDesc>) -> SeqBuilder {
+        let exact = seq
+            .iter()
+            .filter(|desc| desc.sq.chars().all(|c| matches!(c, 'A' | 'C' | 'G' | 'T')))
+            .cloned()
+            .collect();
+
+        let iupac = seq
+            .iter()
+            .filter(|desc| {
+                desc.sq.chars().any(|c| {
+                    matches!(
+                        c,
+                        'R' | 'Y' | 'M' | 'K' | 'S' | 'W' | 'B' | 'D' | 'H' | 'V' | 'N'
+                    )
+                })
+            })
+            .cloned()
+            .collect();
+
+        SeqBuilder {
+            exact,
+            iupac,
+            all: seq,
+        }
+    }
+}
+
 /// This struct is structured according to [place.seq]
 ///
-#[derive(Decode, Debug, Clone)]
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
 pub struct SeqDesc {
     pub id: String,
-    pub ac: String,      // accession number
-    pub dt: String,      // date
-    pub de: String,      // description
-    pub kw: Vec<String>, // keywords
-    pub os: String,      // organism source
-    pub ra: String,      // reference authors
-    pub rt: String,      // reference title
-    pub rl: String,      // reference location
-    pub rd: String,      // reference details
-    pub rc: String,      // reference comments
-    pub sq: String,      // sequence
+    pub ac: String,           // accession number
+    pub dt: String,           // date
+    pub de: String,           // description
+    pub kw: Vec<String>,      // keywords
+    pub os: String,           // organism source
+    pub refs: Vec<Reference>, // references, one per RA/RT/RL/RD/RC block
+    pub sq: String,           // sequence
 }
 
-impl std::fmt::Display for SeqDesc {
+/// One reference block of an entry.
+#[derive(Encode, Decode, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reference {
+    pub authors: String,      // RA
+    pub title: String,        // RT
+    pub location: String,     // RL
+    pub details: String,      // RD, as written
+    pub pubmed: Vec<String>,  // PubMed IDs parsed from RD
+    pub genbank: Vec<String>, // GenBank accessions parsed from RD
+    pub comments: String,     // RC
+}
+
+impl Reference {
+    /// Set the `RD` line and parse the cross-references in it.
+    ///
+    /// `RD` looks like `PubMed: 1921969; GenBank: M62855, M62856;`,
+    /// values without a key belong to the previous key.
+    pub fn set_details(&mut self, details: &str) {
+        self.details = details.to_string();
+        self.pubmed.clear();
+        self.genbank.clear();
+
+        let mut key = "";
+        for part in details.split(';') {
+            let values = match part.split_once(':') {
+                Some((k, v)) => {
+                    key = k.trim();
+                    v
+                }
+                None => part,
+            };
+            let values = values
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|v| !v.is_empty())
+                .map(|v| v.to_string());
+
+            match key.to_ascii_lowercase().as_str() {
+                "pubmed" => self.pubmed.extend(values),
+                "genbank" => self.genbank.extend(values),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Reference {
+    /// A one line citation: authors, title, location and the PubMed IDs.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<&str> = [&self.authors, &self.title, &self.location]
+            .into_iter()
+            .map(|s| s.trim_end_matches('.'))
+            .filter(|s| !s.is_empty())
+            .collect();
+        write!(f, "{}", parts.join(". "))?;
+        if !self.pubmed.is_empty() {
+            write!(f, " [PubMed: {}]", self.pubmed.join(", "))?;
+        }
+        Ok(())
+    }
+}
 
-        let header = format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
-            "ID", "Accession", "Date", "Description", "Keywords", "Organism Source",
-            "Reference Authors", "Reference Title", "Reference Location",
-            "Reference Details", "Reference Comments", "Sequence");
+impl std::fmt::Display for SeqDesc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let header = format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            "ID",
+            "Accession",
+            "Date",
+            "Description",
+            "Keywords",
+            "Organism Source",
+            "Reference Authors",
+            "Reference Title",
+            "Reference Location",
+            "Reference Details",
+            "Reference Comments",
+            "Sequence"
+        );
 
-            let row = format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
-            self.id, self.ac, self.dt, self.de,
-            self.kw.join(", "), self.os, self.ra, self.rt, self.rl,
-            self.rd, self.rc, self.sq);
+        // Several references are separated by ` | `
+        let refs = |field: fn(&Reference) -> &str| {
+            self.refs.iter().map(field).collect::<Vec<_>>().join(" | ")
+        };
+        let row = format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            self.id,
+            self.ac,
+            self.dt,
+            self.de,
+            self.kw.join(", "),
+            self.os,
+            refs(|r| &r.authors),
+            refs(|r| &r.title),
+            refs(|r| &r.location),
+            refs(|r| &r.details),
+            refs(|r| &r.comments),
+            self.sq
+        );
 
         write!(f, "{}{}", header, row)
     }
@@ -65,9 +193,24 @@ impl std::fmt::Display for SeqDesc {
 /// The index of the PLACE database.
 /// Make it faster to fetch info
 /// by those 2 keys.
-#[derive(Decode, Debug, Clone)]
+#[derive(Encode, Decode, Debug, Clone)]
 pub struct PlaceIndex {
     pub id_index: HashMap<String, usize>,
     pub ac_index: HashMap<String, usize>,
     // pub sq_index: HashMap<String, usize>  // Not unique
 }
+
+impl PlaceIndex {
+    /// Index the elements by ID and AC, pointing into `SeqBuilder::all`.
+    pub fn from_descs(descs: &[SeqDesc]) -> PlaceIndex {
+        let mut id_index = HashMap::new();
+        let mut ac_index = HashMap::new();
+
+        for (p, desc) in descs.iter().enumerate() {
+            id_index.insert(desc.id.clone(), p);
+            ac_index.insert(desc.ac.clone(), p);
+        }
+
+        Self { id_index, ac_index }
+    }
+}
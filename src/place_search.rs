@@ -1,16 +1,81 @@
 //!
 //! This module is responsible for searching the PLACE database.
 
-use crate::db::IUPAC_MAP;
-use crate::io::{RecordDesc, SearchResult};
+use rayon::prelude::*;
+use std::io::BufRead;
+
+use crate::annotation::AnnotationQuery;
+use crate::coords::Coordinates;
+use crate::db::Database;
+use crate::error::Error;
+use crate::filter::ElementFilter;
+use crate::io::{FastaChunks, RecordDesc, SearchResult};
+use crate::matcher::{AmbiguityPolicy, PatternHit, mismatch_positions};
 use crate::place_desc::SeqDesc;
 use crate::{db::PLACE_DB, io::SearchedDesc};
-use rayon::prelude::*;
-
-use std::sync::Mutex;
 
 pub struct Search;
 
+/// Total length of the query from which the records are searched in parallel.
+const PARALLEL_MIN_LEN: usize = 64 * 1024;
+
+/// Bases of a record searched at once by `search_stream`.
+const STREAM_CHUNK_LEN: usize = 1 << 20;
+
+/// Options of a search run.
+///
+/// The default options only report perfect (IUPAC-aware) matches.
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// Mismatches allowed per element (Hamming distance).
+    pub max_mismatches: usize,
+    /// Only elements at least this long are searched with mismatches,
+    /// shorter ones still need a perfect match.
+    ///
+    /// Elements not longer than `max_mismatches` always need a perfect match,
+    /// otherwise they'd hit everywhere.
+    pub mismatch_min_len: usize,
+    /// How ambiguous IUPAC bases (N, R, Y...) in the query are matched.
+    pub ambiguity: AmbiguityPolicy,
+    /// Only the elements passing the filter are reported.
+    ///
+    /// The matchers of the database are shared, so the filter doesn't shrink them:
+    /// a group of elements (exact or IUPAC) is only skipped when none of its elements pass,
+    /// otherwise the query is scanned for all of them and the output is filtered.
+    pub filter: ElementFilter,
+}
+
+impl SearchOptions {
+    /// Whether an element of this length may be reported with mismatches.
+    fn allows_mismatches(&self, len: usize) -> bool {
+        len > self.max_mismatches && len >= self.mismatch_min_len
+    }
+}
+
+/// The elements of each group passing the filter, computed once per run.
+///
+/// `None` keeps every element of the group.
+struct ElementMasks {
+    exact: Option<Vec<bool>>,
+    iupac: Option<Vec<bool>>,
+}
+
+impl ElementMasks {
+    /// Check the filter of the options against the database and apply it to the elements.
+    fn new(db: &Database, options: &SearchOptions) -> Result<Self, Error> {
+        options.filter.check_names(db)?;
+        Ok(Self {
+            exact: options.filter.mask(&db.seq_desc.exact),
+            iupac: options.filter.mask(&db.seq_desc.iupac),
+        })
+    }
+
+    /// Whether a group has an element to report, so its matcher has to run.
+    fn scans(mask: Option<&[bool]>) -> bool {
+        mask.is_none_or(|mask| mask.contains(&true))
+    }
+}
+
 impl Search {
     /// The function is to search element in PLACE database,
     /// by input your query string that is fasta format.
@@ -18,168 +83,249 @@ impl Search {
     /// When there's multiple sequences in the query,
     /// The function will run multiple threads to increase the speed.
     ///
-    pub fn search_elements(
-        query: &[RecordDesc],
-    ) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
-        let mut res_p: Vec<SearchResult> = Vec::new();
-
-        // not gonna use rayon as it's a overhead for small data.
-        query
-            .iter()
-            .for_each(|seq| match Self::search_elements_single_seq(&seq) {
-                Ok(r) => {
-                    res_p.extend(r);
-                }
-                Err(e) => {
-                    println!("Error: {}", e);
+    pub fn search_elements(query: &[RecordDesc]) -> Result<Vec<SearchResult<'_>>, Error> {
+        Self::search_elements_with(&PLACE_DB, query, &SearchOptions::default())
+    }
+
+    /// Same as `search_elements`, on any database and with the options of the run.
+    ///
+    /// The records are searched in parallel when there are several of them
+    /// and enough bases to pay for the threads, the results keep the order of the records.
+    pub fn search_elements_with<'a>(
+        db: &'a Database,
+        query: &'a [RecordDesc],
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult<'a>>, Error> {
+        let masks = ElementMasks::new(db, options)?;
+        let search = |seq: &'a RecordDesc| Self::search_record(db, seq, options, &masks);
+
+        // A small query is faster on the current thread than with rayon's overhead.
+        let total_len: usize = query.iter().map(|seq| seq.len()).sum();
+        let results: Vec<SearchResult> = if query.len() > 1 && total_len >= PARALLEL_MIN_LEN {
+            query.par_iter().map(search).collect::<Result<_, _>>()?
+        } else {
+            query.iter().map(search).collect::<Result<_, _>>()?
+        };
+
+        Ok(results)
+    }
+
+    /// Search a FASTA stream of any size, with bounded memory.
+    ///
+    /// The records are read in overlapping chunks and never held whole,
+    /// each hit is given to `on_hit` as soon as its chunk is searched.
+    /// Positions are relative to the start of the record, like `search_elements` gives.
+    pub fn search_stream<R, F>(reader: R, on_hit: F) -> Result<(), Error>
+    where
+        R: BufRead,
+        F: FnMut(SearchedDesc<'_>) -> std::io::Result<()>,
+    {
+        Self::search_stream_with(&PLACE_DB, reader, &SearchOptions::default(), on_hit)
+    }
+
+    /// Same as `search_stream`, on any database and with the options of the run.
+    pub fn search_stream_with<R, F>(
+        db: &Database,
+        reader: R,
+        options: &SearchOptions,
+        on_hit: F,
+    ) -> Result<(), Error>
+    where
+        R: BufRead,
+        F: FnMut(SearchedDesc<'_>) -> std::io::Result<()>,
+    {
+        Self::search_stream_chunked(db, reader, options, STREAM_CHUNK_LEN, on_hit)
+    }
+
+    /// Same as `search_stream_with`, reading `chunk_len` bases of a record at once
+    /// (at least 1) instead of 1 Mb.
+    ///
+    /// The hits don't depend on the chunk length, only the memory used does.
+    pub fn search_stream_chunked<R, F>(
+        db: &Database,
+        reader: R,
+        options: &SearchOptions,
+        chunk_len: usize,
+        mut on_hit: F,
+    ) -> Result<(), Error>
+    where
+        R: BufRead,
+        F: FnMut(SearchedDesc<'_>) -> std::io::Result<()>,
+    {
+        let masks = ElementMasks::new(db, options)?;
+        // A hit starting in a chunk always ends before the end of its overlap
+        let overlap = db.max_element_len().saturating_sub(1);
+
+        for chunk in FastaChunks::new(reader, chunk_len, overlap) {
+            let chunk = chunk?;
+            for hit in Self::search_record(db, &chunk.record, options, &masks)?.search_descs {
+                // The hits starting in the overlap belong to the next chunk
+                if hit.q_coords.start() >= chunk.core_len {
+                    continue;
                 }
-            });
+                on_hit(hit.shift(chunk.offset))?;
+            }
+        }
 
-        let res = res_p.to_owned();
-        Ok(res)
+        Ok(())
     }
 
     /// The function is to search on 1 sequence.
-    pub fn search_elements_single_seq(
-        query: &RecordDesc,
-    ) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    pub fn search_elements_single_seq(query: &RecordDesc) -> Result<Vec<SearchResult<'_>>, Error> {
+        Self::search_elements_single_seq_with(&PLACE_DB, query, &SearchOptions::default())
+    }
+
+    /// Same as `search_elements_single_seq`, on any database and with the options of the run.
+    pub fn search_elements_single_seq_with<'a>(
+        db: &'a Database,
+        query: &'a RecordDesc,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult<'a>>, Error> {
+        let masks = ElementMasks::new(db, options)?;
+        Ok(vec![Self::search_record(db, query, options, &masks)?])
+    }
+
+    /// Search one record, the options being already checked.
+    fn search_record<'a>(
+        db: &'a Database,
+        query: &'a RecordDesc,
+        options: &SearchOptions,
+        masks: &ElementMasks,
+    ) -> Result<SearchResult<'a>, Error> {
         let mut searched: Vec<SearchedDesc> = vec![];
         let pre_size = query.len() / 5;
 
-        let res_exact = Self::search_element_exact(query, pre_size)?;
-        let res_iupac = Self::search_element_iupac(query, pre_size)?;
-        searched.extend(res_exact);
-        searched.extend(res_iupac);
-        searched.sort_unstable_by(|a, b| a.q_start.cmp(&b.q_start));
-
-        let total = vec![SearchResult::new(
-            &query.id(), // id
-            searched,    // search results
-        )];
+        if options.max_mismatches == 0 && options.ambiguity != AmbiguityPolicy::Permissive {
+            searched.extend(Self::search_element_exact(
+                db,
+                query,
+                options,
+                masks.exact.as_deref(),
+                pre_size,
+            )?);
+            searched.extend(Self::search_element_iupac(
+                db,
+                query,
+                options,
+                masks.iupac.as_deref(),
+                pre_size,
+            )?);
+        } else {
+            searched.extend(Self::search_element_bitwise(
+                db, query, options, masks, pre_size,
+            )?);
+        }
+        searched.sort_unstable_by_key(|a| a.q_coords.start());
 
-        Ok(total)
+        Ok(SearchResult::new(
+            query.id(), // id
+            searched,   // search results
+        ))
     }
 
-    /// Search element by exact match with KMP algorithm.
-    fn search_element_exact(
-        query: &RecordDesc,
+    /// Search element by exact match with the Aho-Corasick automaton.
+    ///
+    /// All exact elements and their reverse complements are found
+    /// in a single pass over the query.
+    fn search_element_exact<'a>(
+        db: &'a Database,
+        query: &'a RecordDesc,
+        options: &SearchOptions,
+        mask: Option<&[bool]>,
         presize: usize,
-    ) -> Result<Vec<SearchedDesc>, Box<dyn std::error::Error>> {
-        let seqs = &PLACE_DB.seq_desc.exact;
-        let descs = Mutex::new(Vec::with_capacity(presize));
-
-        // Search the forward sequence
-        seqs.par_iter().for_each(|pattern| {
-            let matches = Self::kmp_search(&query.seq(), &pattern.sq);
-            let mut partial_descs = Vec::with_capacity(matches.len());
-            for &start in &matches {
-                let end = start + pattern.sq.len();
-                let searched = SearchedDesc::new(
-                    &query.id(),      // id
-                    start + 1,        // start position (1-based)
-                    end + 1,          // end position (1-based)
-                    1,                // sequence direction
-                    &pattern.id,      // element id
-                    pattern.sq.len(), // element length
-                    &pattern.sq,      // element sequence
-                    &pattern.ac,      // element accession number
-                    &pattern.de,      // element description
-                );
-                partial_descs.push(searched);
-            }
-            descs.lock().unwrap().extend(partial_descs);
-        });
-
-        // Search the reverse complement sequence
-        let reverse = Self::reverse_complement(&query.seq());
-        seqs.par_iter().for_each(|pattern| {
-            let matches = Self::kmp_search(&reverse, &pattern.sq);
-            let mut partial_descs = Vec::with_capacity(matches.len());
-            for &start in &matches {
-                let end = start + pattern.sq.len();
-                let searched = SearchedDesc::new(
-                    &query.id(),             // id
-                    query.len() + 1 - end,   // start position
-                    query.len() + 1 - start, // end position
-                    0,                       // sequence direction
-                    &pattern.id,             // element id
-                    pattern.sq.len(),        // element length
-                    &pattern.sq,             // element sequence
-                    &pattern.ac,             // element accession number
-                    &pattern.de,             // element description
-                );
-                partial_descs.push(searched);
-            }
-            descs.lock().unwrap().extend(partial_descs);
-        });
+    ) -> Result<Vec<SearchedDesc<'a>>, Error> {
+        if !ElementMasks::scans(mask) {
+            return Ok(vec![]);
+        }
+        let seqs = &db.seq_desc.exact;
+        let hits = db.exact_automaton().find_all(query.seq_bytes());
+        Ok(Self::hits_to_descs(
+            query, seqs, hits, options, mask, presize,
+        ))
+    }
 
-        Ok(descs.into_inner().unwrap())
+    /// Search element by IUPAC match with the bit-parallel matcher.
+    ///
+    /// All IUPAC elements and their reverse complements are found
+    /// in a single pass over the query.
+    fn search_element_iupac<'a>(
+        db: &'a Database,
+        query: &'a RecordDesc,
+        options: &SearchOptions,
+        mask: Option<&[bool]>,
+        presize: usize,
+    ) -> Result<Vec<SearchedDesc<'a>>, Error> {
+        if !ElementMasks::scans(mask) {
+            return Ok(vec![]);
+        }
+        let seqs = &db.seq_desc.iupac;
+        let hits = db.iupac_matcher().find_all(query.seq_bytes());
+        Ok(Self::hits_to_descs(
+            query, seqs, hits, options, mask, presize,
+        ))
     }
 
-    /// Search element by IUPAC match with KMP-based pattern matching.
-    fn search_element_iupac(
-        query: &RecordDesc,
+    /// Search element allowing mismatches or permissive ambiguous bases,
+    /// with the bit-parallel matchers.
+    ///
+    /// Both the exact and the IUPAC elements go through the shift-and matchers here,
+    /// the hits of elements not allowed to mismatch are kept only when they're perfect.
+    fn search_element_bitwise<'a>(
+        db: &'a Database,
+        query: &'a RecordDesc,
+        options: &SearchOptions,
+        masks: &ElementMasks,
         presize: usize,
-    ) -> Result<Vec<SearchedDesc>, Box<dyn std::error::Error>> {
-        let seqs = &PLACE_DB.seq_desc.iupac;
-        let descs = Mutex::new(Vec::with_capacity(presize));
-
-        seqs.par_iter().for_each(|pattern| {
-            // Search the forward sequence
-            let matches = Self::kmp_search_with_iupac(&query.seq(), &pattern.sq);
-            let mut partial_descs = Vec::with_capacity(matches.len());
-            for &start in &matches {
-                let end = start + pattern.sq.len();
-                let searched = SearchedDesc::new(
-                    &query.id(),  // id
-                    start + 1,    // start position (1-based)
-                    end + 1,      // end position (1-based)
-                    1,            // sequence direction
-                    &pattern.id,      // element id
-                    pattern.sq.len(), // element length
-                    &pattern.sq,      // element sequence
-                    &pattern.ac,      // element accession number
-                    &pattern.de,      // element description
-                );
-                partial_descs.push(searched);
-            }
-            descs.lock().unwrap().extend(partial_descs);
-        });
-
-        seqs.par_iter().for_each(|pattern| {
-            // Search the reverse complement sequence
-            let reverse = Self::reverse_complement(&query.seq());
-            let matches = Self::kmp_search_with_iupac(&reverse, &pattern.sq);
-            let mut partial_descs = Vec::with_capacity(matches.len());
-            for &start in &matches {
-                let end = start + pattern.sq.len();
-                let searched = SearchedDesc::new(
-                    &query.id(),             // id
-                    query.len() + 1 - end,   // start position
-                    query.len() + 1 - start, // end position
-                    0,                       // sequence direction
-                    &pattern.id,                 // element id
-                    pattern.sq.len(),            // element length
-                    &pattern.sq,                 // element sequence
-                    &pattern.ac,                 // element accession number
-                    &pattern.de,                 // element description
-                );
-                partial_descs.push(searched);
-            }
-            descs.lock().unwrap().extend(partial_descs);
-        });
+    ) -> Result<Vec<SearchedDesc<'a>>, Error> {
+        let mut descs = Vec::with_capacity(presize);
+        let exact = masks.exact.as_deref();
+        let iupac = masks.iupac.as_deref();
+        // The exact matcher is built on first use, only get it when one of its elements is kept
+        let groups = [
+            (
+                &db.seq_desc.exact,
+                exact,
+                ElementMasks::scans(exact).then(|| db.exact_bitwise_matcher()),
+            ),
+            (
+                &db.seq_desc.iupac,
+                iupac,
+                ElementMasks::scans(iupac).then(|| db.iupac_matcher()),
+            ),
+        ];
+
+        for (seqs, mask, matcher) in groups {
+            let Some(matcher) = matcher else {
+                continue;
+            };
+            let hits: Vec<PatternHit> = matcher
+                .find_within(query.seq_bytes(), options.max_mismatches, options.ambiguity)
+                .into_iter()
+                .filter(|hit| {
+                    hit.mismatches == 0 || options.allows_mismatches(seqs[hit.element].sq.len())
+                })
+                .collect();
+            descs.extend(Self::hits_to_descs(
+                query, seqs, hits, options, mask, presize,
+            ));
+        }
 
-        Ok(descs.into_inner().unwrap())
+        Ok(descs)
     }
 
     /// Query elements by ID.
     pub fn query_elements_by_id(query: &[&str]) -> Vec<Option<SeqDesc>> {
-        let map = &PLACE_DB.seq_index.id_index;
+        Self::query_elements_by_id_with(&PLACE_DB, query)
+    }
+
+    /// Same as `query_elements_by_id`, on any database.
+    pub fn query_elements_by_id_with(db: &Database, query: &[&str]) -> Vec<Option<SeqDesc>> {
+        let map = &db.seq_index.id_index;
         let mut elements = Vec::new();
 
         for &id in query {
             if let Some(&index) = map.get(id) {
-                elements.push(Some(PLACE_DB.seq_desc.all[index].clone()));
+                elements.push(Some(db.seq_desc.all[index].clone()));
             } else {
                 elements.push(None);
             }
@@ -187,15 +333,42 @@ impl Search {
         elements
     }
 
+    /// Query elements by their keywords, organism and description.
+    ///
+    /// The query is parsed by `AnnotationQuery::parse`, e.g. `kw:auxin OR de:"auxin response"`.
+    /// Matching elements are returned in database order.
+    pub fn query_elements_by_annotation(query: &str) -> Result<Vec<SeqDesc>, Error> {
+        Self::query_elements_by_annotation_with(&PLACE_DB, query)
+    }
+
+    /// Same as `query_elements_by_annotation`, on any database.
+    pub fn query_elements_by_annotation_with(
+        db: &Database,
+        query: &str,
+    ) -> Result<Vec<SeqDesc>, Error> {
+        let query = AnnotationQuery::parse(query)?;
+        let elements = db
+            .annotation_index()
+            .search(&query)
+            .into_iter()
+            .map(|index| db.seq_desc.all[index].clone())
+            .collect();
+        Ok(elements)
+    }
+
     /// Query elements by AC.
     pub fn query_elements_by_ac(query: &[&str]) -> Vec<Option<SeqDesc>> {
-        println!("{:?}", query);
-        let map = &PLACE_DB.seq_index.ac_index;
+        Self::query_elements_by_ac_with(&PLACE_DB, query)
+    }
+
+    /// Same as `query_elements_by_ac`, on any database.
+    pub fn query_elements_by_ac_with(db: &Database, query: &[&str]) -> Vec<Option<SeqDesc>> {
+        let map = &db.seq_index.ac_index;
         let mut elements = Vec::new();
 
         for &ac in query {
             if let Some(&index) = map.get(ac) {
-                elements.push(Some(PLACE_DB.seq_desc.all[index].clone()));
+                elements.push(Some(db.seq_desc.all[index].clone()));
             } else {
                 elements.push(None);
             }
@@ -204,145 +377,54 @@ impl Search {
     }
 }
 
-/// KMP Algorithm
+/// Tool functions
 impl Search {
-    /// Compute the longest prefix-suffix (LPS) array
-    fn compute_lps(pattern: &str) -> Vec<usize> {
-        let chars: Vec<char> = pattern.chars().collect();
-        let n = chars.len();
-        let mut lps = vec![0; n];
-
-        let mut len = 0;
-        let mut i = 1;
-
-        while i < n {
-            if chars[i] == chars[len] {
-                len += 1;
-                lps[i] = len;
-                i += 1;
-            } else {
-                if len != 0 {
-                    len = lps[len - 1];
-                } else {
-                    lps[i] = 0;
-                    i += 1;
-                }
-            }
-        }
-
-        lps
-    }
-
-    /// KMP search algorithm
-    fn kmp_search(text: &str, pattern: &str) -> Vec<usize> {
-        let mut matches = Vec::new();
-        if pattern.is_empty() {
-            return matches;
-        }
-
-        let text_chars: Vec<char> = text.chars().collect();
-        let pattern_chars: Vec<char> = pattern.chars().collect();
-
-        let n = text_chars.len();
-        let m = pattern_chars.len();
-
-        if m > n {
-            return matches;
-        }
-
-        let lps = Self::compute_lps(pattern);
-
-        let mut i = 0; // Text pointer
-        let mut j = 0; // Pattern pointer
+    /// Turn the hits of a matcher into searched descriptions,
+    /// dropping the elements rejected by the filter (`mask` over `seqs`)
+    /// and the windows the ambiguity policy doesn't keep.
+    fn hits_to_descs<'a>(
+        query: &'a RecordDesc,
+        seqs: &'a [SeqDesc],
+        hits: Vec<PatternHit>,
+        options: &SearchOptions,
+        mask: Option<&[bool]>,
+        presize: usize,
+    ) -> Vec<SearchedDesc<'a>> {
+        let mut descs = Vec::with_capacity(presize.max(hits.len()));
 
-        while i < n {
-            if pattern_chars[j] == text_chars[i] {
-                i += 1;
-                j += 1;
+        for hit in hits {
+            if mask.is_some_and(|mask| !mask[hit.element]) {
+                continue;
             }
-
-            if j == m {
-                matches.push(i - j);
-                j = lps[j - 1];
-            } else if i < n && pattern_chars[j] != text_chars[i] {
-                if j != 0 {
-                    j = lps[j - 1];
-                } else {
-                    i += 1;
-                }
+            let pattern = &seqs[hit.element];
+            let end = hit.start + pattern.sq.len();
+            if !options.ambiguity.keeps(&query.seq_bytes()[hit.start..end]) {
+                continue;
             }
-        }
-
-        matches
-    }
-
-    /// KMP search algorithm with IUPAC support
-    fn kmp_search_with_iupac(text: &str, pattern: &str) -> Vec<usize> {
-        let mut matches = Vec::new();
-        if pattern.is_empty() {
-            return matches;
-        }
-
-        let text_chars: Vec<char> = text.chars().collect();
-        let pattern_chars: Vec<char> = pattern.chars().collect();
-
-        let n = text_chars.len();
-        let m = pattern_chars.len();
-
-        if m > n {
-            return matches;
-        }
-
-        // Check if the pattern matches
-        'outer: for i in 0..=n - m {
-            for j in 0..m {
-                if !Self::is_iupac_match(text_chars[i + j], pattern_chars[j]) {
-                    continue 'outer;
-                }
-            }
-            matches.push(i);
-        }
-
-        matches
-    }
-}
-
-/// Tool functions
-impl Search {
-    /// Check if a character in the text matches the IUPAC pattern
-    fn is_iupac_match(text_char: char, pattern_char: char) -> bool {
-        if pattern_char == text_char {
-            return true;
-        }
-
-        if let Some(pattern) = IUPAC_MAP.get(&pattern_char) {
-            let regex_pattern = pattern.to_string();
-            if regex_pattern.len() == 1 {
-                return regex_pattern.chars().next().unwrap() == text_char;
-            } else {
-                let mut chars_inside_brackets = regex_pattern
-                    .trim_start_matches('[')
-                    .trim_end_matches(']')
-                    .chars();
-                return chars_inside_brackets.any(|c| c == text_char);
+            let mut searched = SearchedDesc::new(
+                query.id(),                                   // id
+                Coordinates::from_zero_based(hit.start, end), // span on the query
+                hit.strand.into(),                            // strand
+                &pattern.id,                                  // element id
+                pattern.sq.len(),                             // element length
+                &pattern.sq,                                  // element sequence
+                &pattern.ac,                                  // element accession number
+                &pattern.de,                                  // element description
+            )
+            .with_anchor(query.anchor())
+            .with_source(query.source());
+            if hit.mismatches > 0 {
+                let positions = mismatch_positions(
+                    query.seq_bytes(),
+                    &hit,
+                    pattern.sq.as_bytes(),
+                    options.ambiguity,
+                );
+                searched = searched.with_mismatches(positions);
             }
+            descs.push(searched);
         }
 
-        false
-    }
-
-    /// Get reverse complement chain
-    fn reverse_complement(query: &str) -> String {
-        let mut rev_comp = String::with_capacity(query.len());
-        for c in query.chars().rev() {
-            match c {
-                'A' => rev_comp.push('T'),
-                'T' => rev_comp.push('A'),
-                'C' => rev_comp.push('G'),
-                'G' => rev_comp.push('C'),
-                _ => rev_comp.push(c),
-            }
-        }
-        rev_comp
+        descs
     }
 }
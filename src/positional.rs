@@ -0,0 +1,448 @@
+//!
+//! Where the elements occur relative to the TSS of a set of promoters.
+//!
+//! Each hit is placed at its centre, as an offset from the anchor of its query
+//! (the TSS or ATG, see `RecordDesc::anchor`), and counted in bins of `bin` bases
+//! aligned on the anchor, by strand. The queries without an anchor are left out.
+//!
+//! An element is biased towards the TSS when more of its hits fall within `near` bases
+//! of the anchor than a uniform spread over the queries gives:
+//! the share of the query bases in that window. The one-sided binomial p-values
+//! are corrected with Benjamini-Hochberg.
+//!
+//! Positions are written relative to the anchor, `+1` at the anchor, `-1` before it.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::coords::{Strand, relative_offset};
+use crate::enrichment::{LogFactorials, benjamini_hochberg};
+use crate::io::{RecordDesc, SearchResult};
+
+/// The bins and the window of the positional-bias test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionalOptions {
+    /// Bases per bin.
+    pub bin: usize,
+    /// Bases on each side of the anchor counted as near it.
+    pub near: usize,
+    /// Elements with fewer hits aren't reported.
+    pub min_hits: usize,
+}
+
+impl Default for PositionalOptions {
+    fn default() -> Self {
+        Self {
+            bin: 50,
+            near: 100,
+            min_hits: 5,
+        }
+    }
+}
+
+/// The histogram and the positional bias of one element.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElementPositions {
+    pub element_id: String,
+    /// Hits on the plus strand per bin.
+    pub plus: Vec<usize>,
+    /// Hits on the minus strand per bin.
+    pub minus: Vec<usize>,
+    pub hits: usize,
+    /// Hits within `near` bases of the anchor.
+    pub near_hits: usize,
+    /// Hits expected there from a uniform spread.
+    pub expected_near: f64,
+    /// `near_hits` over `expected_near`.
+    pub fold: f64,
+    /// The bin with the most hits.
+    pub peak: usize,
+    /// One-sided binomial p-value of more hits near the anchor.
+    pub p_value: f64,
+    /// Benjamini-Hochberg adjusted p-value.
+    pub q_value: f64,
+}
+
+impl ElementPositions {
+    /// Hits of both strands in a bin.
+    pub fn total(&self, bin: usize) -> usize {
+        self.plus[bin] + self.minus[bin]
+    }
+}
+
+/// The positions of the elements in a set of anchored queries, by increasing p-value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Positional {
+    /// Bases per bin.
+    pub bin: usize,
+    /// Offset from the anchor of the first base of the first bin.
+    pub first: isize,
+    /// Query bases in each bin, the uniform expectation.
+    pub coverage: Vec<usize>,
+    /// Queries with an anchor.
+    pub queries: usize,
+    pub elements: Vec<ElementPositions>,
+}
+
+impl Positional {
+    /// The first and the last position of a bin, relative to the anchor.
+    pub fn bin_range(&self, bin: usize) -> (isize, isize) {
+        let start = self.first + (bin * self.bin) as isize;
+        let end = start + self.bin as isize - 1;
+        (relative_offset(start), relative_offset(end))
+    }
+
+    /// Hits of an element expected in a bin from a uniform spread.
+    pub fn expected(&self, element: &ElementPositions, bin: usize) -> f64 {
+        let total: usize = self.coverage.iter().sum();
+        if total == 0 {
+            return 0.0;
+        }
+        element.hits as f64 * self.coverage[bin] as f64 / total as f64
+    }
+
+    /// Write the histograms as a TSV table, one line per element and bin.
+    pub fn write_histograms<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "Element ID\tBin Start\tBin End\tPlus\tMinus\tTotal\tExpected"
+        )?;
+        for element in &self.elements {
+            for bin in 0..self.coverage.len() {
+                let (start, end) = self.bin_range(bin);
+                writeln!(
+                    writer,
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{:.3}",
+                    element.element_id,
+                    start,
+                    end,
+                    element.plus[bin],
+                    element.minus[bin],
+                    element.total(bin),
+                    self.expected(element, bin)
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Plot the histograms of the first `max_plots` elements as an SVG,
+    /// one panel per element, the hits of each strand stacked and the uniform expectation as a line.
+    pub fn write_svg<W: Write>(&self, mut writer: W, max_plots: usize) -> std::io::Result<()> {
+        const WIDTH: f64 = 640.0;
+        const PANEL: f64 = 140.0;
+        const LEFT: f64 = 50.0;
+        const RIGHT: f64 = 20.0;
+        const TOP: f64 = 25.0;
+        const BOTTOM: f64 = 25.0;
+
+        let elements = &self.elements[..self.elements.len().min(max_plots)];
+        let bins = self.coverage.len().max(1);
+        let plot_width = WIDTH - LEFT - RIGHT;
+        let plot_height = PANEL - TOP - BOTTOM;
+        let bar = plot_width / bins as f64;
+
+        writeln!(
+            writer,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\" font-size=\"11\">",
+            WIDTH,
+            PANEL * elements.len().max(1) as f64
+        )?;
+        writeln!(
+            writer,
+            "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>"
+        )?;
+        writeln!(
+            writer,
+            "<text x=\"{}\" y=\"12\" text-anchor=\"end\"><tspan fill=\"#1f77b4\">plus</tspan> <tspan fill=\"#ff7f0e\">minus</tspan> <tspan fill=\"#555555\">uniform</tspan></text>",
+            WIDTH - RIGHT
+        )?;
+        for (panel, element) in elements.iter().enumerate() {
+            let top = panel as f64 * PANEL + TOP;
+            let base = top + plot_height;
+            let max = (0..self.coverage.len())
+                .map(|bin| (element.total(bin) as f64).max(self.expected(element, bin)))
+                .fold(1.0, f64::max);
+            let scale = plot_height / max;
+
+            writeln!(
+                writer,
+                "<text x=\"{}\" y=\"{:.1}\">{} ({} hits, {} near the TSS, p = {:.2e})</text>",
+                LEFT,
+                top - 8.0,
+                escape(&element.element_id),
+                element.hits,
+                element.near_hits,
+                element.p_value
+            )?;
+            for bin in 0..self.coverage.len() {
+                let x = LEFT + bin as f64 * bar;
+                let plus = element.plus[bin] as f64 * scale;
+                let minus = element.minus[bin] as f64 * scale;
+                if plus > 0.0 {
+                    writeln!(
+                        writer,
+                        "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#1f77b4\"/>",
+                        x,
+                        base - plus,
+                        bar,
+                        plus
+                    )?;
+                }
+                if minus > 0.0 {
+                    writeln!(
+                        writer,
+                        "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#ff7f0e\"/>",
+                        x,
+                        base - plus - minus,
+                        bar,
+                        minus
+                    )?;
+                }
+            }
+
+            let expected: Vec<String> = (0..self.coverage.len())
+                .map(|bin| {
+                    let y = base - self.expected(element, bin) * scale;
+                    format!(
+                        "{:.1},{:.1} {:.1},{:.1}",
+                        LEFT + bin as f64 * bar,
+                        y,
+                        LEFT + (bin + 1) as f64 * bar,
+                        y
+                    )
+                })
+                .collect();
+            writeln!(
+                writer,
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"#555555\" stroke-dasharray=\"4 2\"/>",
+                expected.join(" ")
+            )?;
+
+            // The axis, the anchor and the positions of both ends
+            writeln!(
+                writer,
+                "<line x1=\"{}\" y1=\"{:.1}\" x2=\"{}\" y2=\"{:.1}\" stroke=\"black\"/>",
+                LEFT,
+                base,
+                WIDTH - RIGHT,
+                base
+            )?;
+            let span = (bins * self.bin) as f64;
+            let anchor = LEFT + (-self.first) as f64 / span * plot_width;
+            if (LEFT..=WIDTH - RIGHT).contains(&anchor) {
+                writeln!(
+                    writer,
+                    "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#d62728\"/>",
+                    anchor, top, anchor, base
+                )?;
+                writeln!(
+                    writer,
+                    "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">+1</text>",
+                    anchor,
+                    base + 14.0
+                )?;
+            }
+            if !self.coverage.is_empty() {
+                let (start, _) = self.bin_range(0);
+                let (_, end) = self.bin_range(self.coverage.len() - 1);
+                writeln!(
+                    writer,
+                    "<text x=\"{}\" y=\"{:.1}\">{}</text>",
+                    LEFT,
+                    base + 14.0,
+                    start
+                )?;
+                writeln!(
+                    writer,
+                    "<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+                    WIDTH - RIGHT,
+                    base + 14.0,
+                    end
+                )?;
+            }
+            writeln!(
+                writer,
+                "<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+                LEFT - 5.0,
+                top + 4.0,
+                max.round()
+            )?;
+        }
+        writeln!(writer, "</svg>")
+    }
+}
+
+/// Escape the characters of a text for XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Bases of `[start, end)` within `[low, high)`.
+fn overlap(start: isize, end: isize, low: isize, high: isize) -> usize {
+    (end.min(high) - start.max(low)).max(0) as usize
+}
+
+/// Bin the hits of the anchored queries and test their bias towards the anchor.
+///
+/// `results` are the search results of `records`, in the same order.
+pub fn positional(
+    records: &[RecordDesc],
+    results: &[SearchResult],
+    options: &PositionalOptions,
+) -> Positional {
+    let bin = options.bin.max(1);
+    let anchored: Vec<(&RecordDesc, &SearchResult, isize)> = records
+        .iter()
+        .zip(results)
+        .filter_map(|(record, result)| record.anchor().map(|anchor| (record, result, anchor)))
+        .filter(|(record, _, _)| !record.is_empty())
+        .collect();
+    if anchored.is_empty() {
+        return Positional {
+            bin,
+            ..Default::default()
+        };
+    }
+
+    // The offsets of the queries from their anchors, `[start, end)`
+    let extents: Vec<(isize, isize)> = anchored
+        .iter()
+        .map(|(record, _, anchor)| (-anchor, record.len() as isize - anchor))
+        .collect();
+    let low = extents.iter().map(|e| e.0).min().unwrap_or(0);
+    let high = extents.iter().map(|e| e.1).max().unwrap_or(0);
+    let first = low.div_euclid(bin as isize) * bin as isize;
+    let bins = ((high - first) as usize).div_ceil(bin);
+
+    let coverage: Vec<usize> = (0..bins)
+        .map(|i| {
+            let start = first + (i * bin) as isize;
+            extents
+                .iter()
+                .map(|&(s, e)| overlap(s, e, start, start + bin as isize))
+                .sum()
+        })
+        .collect();
+    let near = options.near as isize;
+    let near_share = {
+        let covered: usize = extents
+            .iter()
+            .map(|&(s, e)| overlap(s, e, -near, near))
+            .sum();
+        let total: usize = coverage.iter().sum();
+        covered as f64 / total as f64
+    };
+
+    // Hits by element, at their centre
+    let mut hits: BTreeMap<&str, (Vec<usize>, Vec<usize>, usize)> = BTreeMap::new();
+    for (_, result, anchor) in &anchored {
+        for desc in &result.search_descs {
+            let start = desc.q_coords.start();
+            let centre = start + desc.q_coords.len().saturating_sub(1) / 2;
+            let offset = centre as isize - anchor;
+            let index = (offset - first).div_euclid(bin as isize);
+            if index < 0 || index as usize >= bins {
+                continue;
+            }
+            let (plus, minus, near_hits) = hits
+                .entry(desc.e_id)
+                .or_insert_with(|| (vec![0; bins], vec![0; bins], 0));
+            match desc.q_strand {
+                Strand::Plus => plus[index as usize] += 1,
+                Strand::Minus => minus[index as usize] += 1,
+            }
+            if (-near..near).contains(&offset) {
+                *near_hits += 1;
+            }
+        }
+    }
+
+    let max_hits = hits
+        .values()
+        .map(|(plus, minus, _)| plus.iter().sum::<usize>() + minus.iter().sum::<usize>())
+        .max()
+        .unwrap_or(0);
+    let log_factorials = LogFactorials::new(max_hits);
+    let mut elements: Vec<ElementPositions> = hits
+        .into_iter()
+        .filter_map(|(id, (plus, minus, near_hits))| {
+            let total = plus.iter().sum::<usize>() + minus.iter().sum::<usize>();
+            if total < options.min_hits.max(1) {
+                return None;
+            }
+            let expected_near = total as f64 * near_share;
+            let peak = (0..bins)
+                .max_by_key(|&i| (plus[i] + minus[i], std::cmp::Reverse(i)))
+                .unwrap_or(0);
+            Some(ElementPositions {
+                element_id: id.to_string(),
+                plus,
+                minus,
+                hits: total,
+                near_hits,
+                expected_near,
+                fold: if expected_near > 0.0 {
+                    near_hits as f64 / expected_near
+                } else {
+                    0.0
+                },
+                peak,
+                p_value: log_factorials.binomial_upper_tail(total, near_hits, near_share),
+                q_value: 1.0,
+            })
+        })
+        .collect();
+
+    let p_values: Vec<f64> = elements.iter().map(|e| e.p_value).collect();
+    for (element, q_value) in elements.iter_mut().zip(benjamini_hochberg(&p_values)) {
+        element.q_value = q_value;
+    }
+    elements.sort_by(|a, b| {
+        a.p_value
+            .total_cmp(&b.p_value)
+            .then_with(|| b.fold.total_cmp(&a.fold))
+            .then_with(|| a.element_id.cmp(&b.element_id))
+    });
+
+    Positional {
+        bin,
+        first,
+        coverage,
+        queries: anchored.len(),
+        elements,
+    }
+}
+
+impl fmt::Display for Positional {
+    /// The positional bias of the elements as a TSV table, with a header line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Element ID\tHits\tNear Hits\tExpected Near\tFold\tPeak Start\tPeak End\tP-value\tQ-value"
+        )?;
+        for e in &self.elements {
+            let (start, end) = self.bin_range(e.peak);
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{:.3}\t{:.3}\t{}\t{}\t{:.3e}\t{:.3e}",
+                e.element_id,
+                e.hits,
+                e.near_hits,
+                e.expected_near,
+                e.fold,
+                start,
+                end,
+                e.p_value,
+                e.q_value
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,454 @@
+//!
+//! Extraction of the promoters of the genes of a genome.
+//!
+//! The genes (or transcripts) are read from a GFF3 or GTF annotation,
+//! their promoter is cut from the genome FASTA around the TSS, on the strand of the gene,
+//! and given as `RecordDesc`s ready for `Search`.
+//!
+//! - GFF3: the `gene` features, or the `mRNA` / `transcript` ones, named by their `ID`,
+//! - GTF: the span of all the lines of a `gene_id`, or of a `transcript_id`,
+//!   so files without `gene` / `transcript` lines work too.
+//!
+//! Features without a strand (`.` or `?`) have no TSS and are skipped.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use crate::coords::{Coordinates, Strand};
+use crate::error::Error;
+use crate::io::RecordDesc;
+use crate::matcher::reverse_complement_bytes;
+
+/// The annotation formats genes are read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationFormat {
+    Gff3,
+    Gtf,
+}
+
+impl AnnotationFormat {
+    /// The format of a file from its extension: `.gff`, `.gff3` or `.gtf`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "gff" | "gff3" => Some(AnnotationFormat::Gff3),
+            "gtf" => Some(AnnotationFormat::Gtf),
+            _ => None,
+        }
+    }
+}
+
+/// The features promoters are extracted for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FeatureLevel {
+    /// One promoter per gene.
+    #[default]
+    Gene,
+    /// One promoter per transcript, alternative TSSs included.
+    Transcript,
+}
+
+/// A gene or a transcript of the annotation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feature {
+    pub id: String,
+    /// The gene of a transcript, from its `Parent` or `gene_id`, the id of a gene.
+    pub gene: String,
+    /// The sequence the feature is on, the first column of the annotation.
+    pub seqid: String,
+    pub coords: Coordinates,
+    pub strand: Strand,
+}
+
+impl Feature {
+    /// The 0-based position of the transcription start site, the first base of the feature.
+    pub fn tss(&self) -> usize {
+        match self.strand {
+            Strand::Plus => self.coords.start(),
+            Strand::Minus => self.coords.end() - 1,
+        }
+    }
+}
+
+/// The genes or the transcripts of an annotation file.
+#[derive(Debug, Clone, Default)]
+pub struct Annotation {
+    pub features: Vec<Feature>,
+}
+
+impl Annotation {
+    /// Read the features of a GFF3 or a GTF annotation.
+    pub fn from_reader<R: BufRead>(
+        reader: R,
+        format: AnnotationFormat,
+        level: FeatureLevel,
+    ) -> Result<Self, Error> {
+        let mut features: Vec<Feature> = vec![];
+        // GTF features are the span of their lines, grouped by id
+        let mut spans: HashMap<String, usize> = HashMap::new();
+
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if format == AnnotationFormat::Gff3 && line.starts_with("##FASTA") {
+                break;
+            }
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = AnnotationLine::parse(&line)
+                .map_err(|e| Error::Annotation(format!("line {}: {}", number + 1, e)))?;
+            let Some(strand) = fields.strand else {
+                continue;
+            };
+
+            match format {
+                AnnotationFormat::Gff3 => {
+                    let wanted = match level {
+                        FeatureLevel::Gene => fields.kind == "gene",
+                        FeatureLevel::Transcript => matches!(fields.kind, "mRNA" | "transcript"),
+                    };
+                    if !wanted {
+                        continue;
+                    }
+                    let id = gff3_attribute(fields.attributes, "ID")
+                        .or_else(|| gff3_attribute(fields.attributes, "Name"))
+                        .unwrap_or_else(|| {
+                            let (start, end) = fields.coords.one_based_closed();
+                            format!("{}:{}-{}", fields.seqid, start, end)
+                        });
+                    let gene = match level {
+                        FeatureLevel::Gene => None,
+                        FeatureLevel::Transcript => gff3_attribute(fields.attributes, "Parent")
+                            .and_then(|parent| parent.split(',').next().map(str::to_string)),
+                    };
+                    features.push(Feature {
+                        gene: gene.unwrap_or_else(|| id.clone()),
+                        id,
+                        seqid: fields.seqid.to_string(),
+                        coords: fields.coords,
+                        strand,
+                    });
+                }
+                AnnotationFormat::Gtf => {
+                    let key = match level {
+                        FeatureLevel::Gene => "gene_id",
+                        FeatureLevel::Transcript => "transcript_id",
+                    };
+                    let Some(id) = gtf_attribute(fields.attributes, key) else {
+                        continue;
+                    };
+                    match spans.get(&id) {
+                        Some(&index) => {
+                            let feature = &mut features[index];
+                            feature.coords = Coordinates::from_zero_based(
+                                feature.coords.start().min(fields.coords.start()),
+                                feature.coords.end().max(fields.coords.end()),
+                            );
+                        }
+                        None => {
+                            spans.insert(id.clone(), features.len());
+                            features.push(Feature {
+                                gene: gtf_attribute(fields.attributes, "gene_id")
+                                    .unwrap_or_else(|| id.clone()),
+                                id,
+                                seqid: fields.seqid.to_string(),
+                                coords: fields.coords,
+                                strand,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(Self { features })
+    }
+
+    /// Read the features of an annotation file, its format given by its extension.
+    pub fn from_file<P>(path: P, level: FeatureLevel) -> Result<Self, Error>
+    where
+        P: AsRef<Path> + std::fmt::Display,
+    {
+        let format = AnnotationFormat::from_path(&path).ok_or_else(|| {
+            Error::Annotation(format!("{}: unknown format, expected .gff3 or .gtf", path))
+        })?;
+        Self::from_file_as(path, format, level)
+    }
+
+    /// Read the features of an annotation file in the given format.
+    pub fn from_file_as<P>(
+        path: P,
+        format: AnnotationFormat,
+        level: FeatureLevel,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path> + std::fmt::Display,
+    {
+        let file = std::fs::File::open(&path)
+            .map_err(|e| Error::Io(std::io::Error::new(e.kind(), format!("{}: {}", path, e))))?;
+        Self::from_reader(std::io::BufReader::new(file), format, level)
+    }
+}
+
+/// The columns of an annotation line used here.
+struct AnnotationLine<'a> {
+    seqid: &'a str,
+    kind: &'a str,
+    coords: Coordinates,
+    strand: Option<Strand>,
+    attributes: &'a str,
+}
+
+impl<'a> AnnotationLine<'a> {
+    fn parse(line: &'a str) -> Result<Self, String> {
+        let columns: Vec<&str> = line.split('\t').collect();
+        if columns.len() < 9 {
+            return Err(format!("expected 9 columns, found {}", columns.len()));
+        }
+        let position = |column: &str| {
+            column
+                .parse::<usize>()
+                .map_err(|_| format!("invalid position '{}'", column))
+        };
+        let (start, end) = (position(columns[3])?, position(columns[4])?);
+        if start == 0 || end < start {
+            return Err(format!("invalid range {}-{}", start, end));
+        }
+        let strand = match columns[6] {
+            "+" => Some(Strand::Plus),
+            "-" => Some(Strand::Minus),
+            "." | "?" => None,
+            other => return Err(format!("invalid strand '{}'", other)),
+        };
+        Ok(Self {
+            seqid: columns[0],
+            kind: columns[2],
+            coords: Coordinates::from_one_based(start, end),
+            strand,
+            attributes: columns[8],
+        })
+    }
+}
+
+/// The value of a GFF3 attribute, `key=value;...`, percent-decoded.
+fn gff3_attribute(attributes: &str, key: &str) -> Option<String> {
+    attributes
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, value)| percent_decode(value))
+}
+
+/// The value of a GTF attribute, `key "value"; ...`.
+fn gtf_attribute(attributes: &str, key: &str) -> Option<String> {
+    attributes
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once(' '))
+        .find(|(k, _)| *k == key)
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| {
+            std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        });
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// How the promoters are cut around the TSS.
+#[derive(Debug, Clone)]
+pub struct PromoterOptions {
+    /// Bases upstream of the TSS.
+    pub upstream: usize,
+    /// Bases downstream of the TSS, the TSS included.
+    pub downstream: usize,
+    /// Stop the upstream region at the end of the nearest other gene before the TSS.
+    pub stop_at_neighbor: bool,
+}
+
+impl Default for PromoterOptions {
+    fn default() -> Self {
+        Self {
+            upstream: 1000,
+            downstream: 0,
+            stop_at_neighbor: false,
+        }
+    }
+}
+
+/// The promoter of a feature.
+#[derive(Debug, Clone)]
+pub struct Promoter {
+    /// The id of the feature.
+    pub id: String,
+    pub seqid: String,
+    /// The span of the promoter on the genome, clipped at the sequence ends.
+    pub coords: Coordinates,
+    pub strand: Strand,
+    /// The 0-based TSS on the genome.
+    pub tss: usize,
+    /// The sequence, reverse-complemented on the minus strand so it reads 5' to 3',
+    /// anchored at the TSS.
+    pub record: RecordDesc,
+}
+
+impl Promoter {
+    /// The index of the TSS in `record`, its length when the TSS isn't in the promoter.
+    pub fn tss_offset(&self) -> usize {
+        match self.strand {
+            Strand::Plus => self.tss - self.coords.start(),
+            Strand::Minus => self.coords.end() - 1 - self.tss,
+        }
+    }
+
+    /// The location on the genome, samtools-style: `chr1:101-200(-)`, 1-based closed.
+    pub fn location(&self) -> String {
+        let (start, end) = self.coords.one_based_closed();
+        format!("{}:{}-{}({})", self.seqid, start, end, self.strand)
+    }
+}
+
+/// Extract the promoters of the features from the genome.
+///
+/// The promoters are in the order of the features,
+/// those clipped to nothing (at a sequence end or against a neighbour) are left out.
+pub fn extract(
+    genome: &[RecordDesc],
+    annotation: &Annotation,
+    options: &PromoterOptions,
+) -> Result<Vec<Promoter>, Error> {
+    let sequences: HashMap<&str, &RecordDesc> =
+        genome.iter().map(|record| (record.id(), record)).collect();
+
+    // The sorted starts and ends of the genes of each sequence, to find the neighbours.
+    // A gene spans all its transcripts, which so never stop each other.
+    let mut bounds: HashMap<&str, (Vec<usize>, Vec<usize>)> = HashMap::new();
+    if options.stop_at_neighbor {
+        let mut genes: HashMap<(&str, &str), (usize, usize)> = HashMap::new();
+        for feature in &annotation.features {
+            let span = genes
+                .entry((&feature.seqid, &feature.gene))
+                .or_insert((feature.coords.start(), feature.coords.end()));
+            span.0 = span.0.min(feature.coords.start());
+            span.1 = span.1.max(feature.coords.end());
+        }
+        for ((seqid, _), (start, end)) in genes {
+            let (starts, ends) = bounds.entry(seqid).or_default();
+            starts.push(start);
+            ends.push(end);
+        }
+        for (starts, ends) in bounds.values_mut() {
+            starts.sort_unstable();
+            ends.sort_unstable();
+        }
+    }
+
+    let mut promoters = Vec::with_capacity(annotation.features.len());
+    for feature in &annotation.features {
+        let record = sequences.get(feature.seqid.as_str()).ok_or_else(|| {
+            Error::Annotation(format!(
+                "{}: sequence {} isn't in the genome",
+                feature.id, feature.seqid
+            ))
+        })?;
+        let tss = feature.tss();
+        if tss >= record.len() {
+            return Err(Error::Annotation(format!(
+                "{}: TSS {} is past the end of {} ({} bases)",
+                feature.id,
+                tss + 1,
+                feature.seqid,
+                record.len()
+            )));
+        }
+
+        let (mut start, mut end) = match feature.strand {
+            Strand::Plus => (
+                tss.saturating_sub(options.upstream),
+                tss + options.downstream,
+            ),
+            Strand::Minus => (
+                (tss + 1).saturating_sub(options.downstream),
+                tss + 1 + options.upstream,
+            ),
+        };
+        end = end.min(record.len());
+
+        if let Some((starts, ends)) = bounds.get(feature.seqid.as_str()) {
+            match feature.strand {
+                // The last end not after the TSS, the feature itself ends after it
+                Strand::Plus => {
+                    let before = ends.partition_point(|&e| e <= tss);
+                    if before > 0 {
+                        start = start.max(ends[before - 1]);
+                    }
+                }
+                // The first start after the TSS, the feature itself starts before it
+                Strand::Minus => {
+                    let after = starts.partition_point(|&s| s <= tss);
+                    if let Some(&next) = starts.get(after) {
+                        end = end.min(next);
+                    }
+                }
+            }
+        }
+        if start >= end {
+            continue;
+        }
+
+        let seq = &record.seq_bytes()[start..end];
+        let seq = match feature.strand {
+            Strand::Plus => seq.to_vec(),
+            Strand::Minus => reverse_complement_bytes(seq),
+        };
+        let mut promoter = Promoter {
+            id: feature.id.clone(),
+            seqid: feature.seqid.clone(),
+            coords: Coordinates::from_zero_based(start, end),
+            strand: feature.strand,
+            tss,
+            record: RecordDesc::from_bytes(&feature.id, &seq),
+        };
+        let anchor = promoter.tss_offset() as isize;
+        promoter.record = promoter.record.with_anchor(anchor);
+        promoters.push(promoter);
+    }
+
+    Ok(promoters)
+}
+
+/// Write the promoters as FASTA, with their location and their TSS as description,
+/// e.g. `>gene1 chr1:101-200(-) tss=101`, read back by `RecordDesc::from_records`.
+pub fn write_fasta<W: Write>(promoters: &[Promoter], writer: W) -> std::io::Result<()> {
+    let mut writer = bio::io::fasta::Writer::new(writer);
+    for promoter in promoters {
+        writer.write(
+            &promoter.id,
+            Some(&format!(
+                "{} tss={}",
+                promoter.location(),
+                promoter.tss_offset() + 1
+            )),
+            promoter.record.seq_bytes(),
+        )?;
+    }
+    writer.flush()
+}
@@ -0,0 +1,268 @@
+//!
+//! Validation and sanitisation of the input sequences.
+//!
+//! The matchers only know the IUPAC DNA alphabet, anything else in a query
+//! (protein letters, `U`, gaps, digits...) silently never matches.
+//! A `Sanitizer` reports these characters and, depending on its policy,
+//! rejects the sequence, keeps it as it is or masks them with `N`.
+
+use std::fmt;
+
+use crate::coords::Coordinates;
+use crate::error::Error;
+use crate::io::RecordDesc;
+use crate::matcher::iupac_mask;
+
+/// At most this many offending positions are written by `SeqReport`'s `Display`.
+const REPORTED_POSITIONS: usize = 10;
+
+/// The alphabet of a sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alphabet {
+    /// `A`, `C`, `G`, `T` only.
+    Dna,
+    /// `A`, `C`, `G`, `U` only.
+    Rna,
+    /// IUPAC nucleotide codes (`N`, `R`, `Y`...), `U` included.
+    Iupac,
+    /// Some characters aren't nucleotide codes.
+    Invalid,
+}
+
+impl Alphabet {
+    /// Classify a sequence, an empty one is DNA.
+    pub fn classify(seq: &[u8]) -> Self {
+        if seq.iter().all(|c| matches!(c, b'A' | b'C' | b'G' | b'T')) {
+            Alphabet::Dna
+        } else if seq.iter().all(|c| matches!(c, b'A' | b'C' | b'G' | b'U')) {
+            Alphabet::Rna
+        } else if seq.iter().all(|&c| is_iupac(c) || c == b'U') {
+            Alphabet::Iupac
+        } else {
+            Alphabet::Invalid
+        }
+    }
+}
+
+impl fmt::Display for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Alphabet::Dna => "DNA",
+            Alphabet::Rna => "RNA",
+            Alphabet::Iupac => "IUPAC",
+            Alphabet::Invalid => "invalid",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What to do with a sequence having characters the matchers don't know.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationPolicy {
+    /// Fail with `Error::InvalidSequence`.
+    Error,
+    /// Search the sequence as it is and report it.
+    #[default]
+    Warn,
+    /// Replace the characters with `N`, so the positions don't change, and report it.
+    Clean,
+}
+
+/// What to do with the gaps (`-` and `.`) of a sequence, e.g. an alignment row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GapMode {
+    /// Keep them, they're handled by the policy like other unknown characters.
+    #[default]
+    Keep,
+    /// Remove them, the positions are then on the ungapped sequence,
+    /// which can't be lifted to its source region anymore.
+    Strip,
+    /// Split the sequence on them, the pieces are named `id:start-end` (1-based, closed).
+    Split,
+}
+
+/// The characters of a sequence which aren't IUPAC DNA codes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeqReport {
+    pub id: String,
+    /// The alphabet of the sequence as given.
+    pub alphabet: Alphabet,
+    /// 1-based positions in the sequence as given, with the offending characters.
+    pub invalid: Vec<(usize, u8)>,
+}
+
+impl SeqReport {
+    /// The report without the id, e.g. `RNA sequence, 1 invalid character(s) at 4 (U)`.
+    fn details(&self) -> String {
+        let mut positions: Vec<String> = self
+            .invalid
+            .iter()
+            .take(REPORTED_POSITIONS)
+            .map(|&(p, c)| format!("{} ({})", p, c.escape_ascii()))
+            .collect();
+        if self.invalid.len() > REPORTED_POSITIONS {
+            positions.push("...".to_string());
+        }
+        format!(
+            "{} sequence, {} invalid character(s) at {}",
+            self.alphabet,
+            self.invalid.len(),
+            positions.join(", ")
+        )
+    }
+}
+
+impl fmt::Display for SeqReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.id, self.details())
+    }
+}
+
+/// The validation and the conversions applied to the sequences of a run.
+#[derive(Debug, Clone, Default)]
+pub struct Sanitizer {
+    pub policy: ValidationPolicy,
+    /// Convert `U` to `T` before validating, for mRNA sequences.
+    pub rna_to_dna: bool,
+    pub gaps: GapMode,
+}
+
+impl Sanitizer {
+    /// Validate and convert one record.
+    ///
+    /// More than one record is returned when the gaps are split,
+    /// the report is `None` when the sequence has no offending character.
+    pub fn sanitize(
+        &self,
+        record: &RecordDesc,
+    ) -> Result<(Vec<RecordDesc>, Option<SeqReport>), Error> {
+        let alphabet = Alphabet::classify(record.seq_bytes());
+        let mut seq = record.seq_bytes().to_vec();
+        if self.rna_to_dna {
+            seq.iter_mut()
+                .filter(|c| **c == b'U')
+                .for_each(|c| *c = b'T');
+        }
+
+        let skip_gaps = self.gaps != GapMode::Keep;
+        let invalid: Vec<(usize, u8)> = seq
+            .iter()
+            .enumerate()
+            .filter(|&(_, &c)| !(is_iupac(c) || skip_gaps && is_gap(c)))
+            .map(|(p, &c)| (p + 1, c))
+            .collect();
+
+        let report = (!invalid.is_empty()).then(|| SeqReport {
+            id: record.id().to_string(),
+            alphabet,
+            invalid,
+        });
+        if let Some(report) = &report {
+            match self.policy {
+                ValidationPolicy::Error => {
+                    return Err(Error::InvalidSequence {
+                        id: report.id.clone(),
+                        message: report.details(),
+                    });
+                }
+                ValidationPolicy::Warn => {}
+                ValidationPolicy::Clean => {
+                    for &(p, _) in &report.invalid {
+                        seq[p - 1] = b'N';
+                    }
+                }
+            }
+        }
+
+        // The anchor follows the bases it was given on
+        let anchored = |piece: RecordDesc, removed: usize| match record.anchor() {
+            Some(anchor) => piece.with_anchor(anchor - removed as isize),
+            None => piece,
+        };
+        // and so does the source region, when the bases keep their positions
+        let sourced = |piece: RecordDesc, span: Coordinates| match record
+            .source()
+            .and_then(|source| source.sub_region(span))
+        {
+            Some(region) => piece.with_source(region),
+            None => Ok(piece),
+        };
+        let records = match self.gaps {
+            GapMode::Keep => {
+                let piece = RecordDesc::from_bytes(record.id(), &seq);
+                vec![sourced(
+                    anchored(piece, 0),
+                    Coordinates::from_zero_based(0, seq.len()),
+                )?]
+            }
+            GapMode::Strip => {
+                let before_anchor = record.anchor().unwrap_or(0).clamp(0, seq.len() as isize);
+                let gaps = seq[..before_anchor as usize]
+                    .iter()
+                    .filter(|&&c| is_gap(c))
+                    .count();
+                let len = seq.len();
+                seq.retain(|&c| !is_gap(c));
+                let piece = anchored(RecordDesc::from_bytes(record.id(), &seq), gaps);
+                if seq.len() == len {
+                    vec![sourced(piece, Coordinates::from_zero_based(0, len))?]
+                } else {
+                    vec![piece]
+                }
+            }
+            GapMode::Split => split_on_gaps(record.id(), &seq)
+                .into_iter()
+                .map(|(start, piece)| {
+                    let span = Coordinates::from_zero_based(start, start + piece.len());
+                    sourced(anchored(piece, start), span)
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        Ok((records, report))
+    }
+
+    /// Validate and convert all the records, with the reports of the offending ones.
+    pub fn sanitize_all(
+        &self,
+        records: &[RecordDesc],
+    ) -> Result<(Vec<RecordDesc>, Vec<SeqReport>), Error> {
+        let mut sanitized = Vec::with_capacity(records.len());
+        let mut reports = vec![];
+        for record in records {
+            let (records, report) = self.sanitize(record)?;
+            sanitized.extend(records);
+            reports.extend(report);
+        }
+        Ok((sanitized, reports))
+    }
+}
+
+/// Whether a byte is an IUPAC DNA code, the alphabet the matchers know.
+fn is_iupac(c: u8) -> bool {
+    iupac_mask(c) != 0
+}
+
+fn is_gap(c: u8) -> bool {
+    c == b'-' || c == b'.'
+}
+
+/// The ungapped pieces of a sequence, named after their 1-based closed range,
+/// with their 0-based start in the sequence.
+///
+/// A sequence without gaps keeps its id.
+fn split_on_gaps(id: &str, seq: &[u8]) -> Vec<(usize, RecordDesc)> {
+    if !seq.iter().any(|&c| is_gap(c)) {
+        return vec![(0, RecordDesc::from_bytes(id, seq))];
+    }
+
+    let mut records = vec![];
+    let mut start = 0;
+    for piece in seq.split(|&c| is_gap(c)) {
+        if !piece.is_empty() {
+            let name = format!("{}:{}-{}", id, start + 1, start + piece.len());
+            records.push((start, RecordDesc::from_bytes(&name, piece)));
+        }
+        start += piece.len() + 1;
+    }
+    records
+}
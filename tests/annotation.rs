@@ -0,0 +1,107 @@
+use placecare::Error;
+use placecare::annotation::{AnnotationIndex, AnnotationQuery, Field};
+use placecare::place_desc::SeqDesc;
+use placecare::place_search::Search;
+
+fn desc(id: &str, kw: &[&str], os: &str, de: &str) -> SeqDesc {
+    SeqDesc {
+        id: id.to_string(),
+        ac: String::new(),
+        dt: String::new(),
+        de: de.to_string(),
+        kw: kw.iter().map(|kw| kw.to_string()).collect(),
+        os: os.to_string(),
+        refs: vec![],
+        sq: "ACGT".to_string(),
+    }
+}
+
+fn index() -> AnnotationIndex {
+    AnnotationIndex::new(&[
+        desc(
+            "A",
+            &["auxin", "sugar"],
+            "rice (Oryza sativa)",
+            "Auxin element",
+        ),
+        desc(
+            "B",
+            &["auxin response; sugar"],
+            "Arabidopsis thaliana",
+            "GT-1 box",
+        ),
+        desc(
+            "C",
+            &["light regulation"],
+            "Arabidopsis thaliana",
+            "Light and sugar",
+        ),
+    ])
+}
+
+fn search(query: &str) -> Vec<usize> {
+    index().search(&AnnotationQuery::parse(query).unwrap())
+}
+
+#[test]
+fn parse_queries() {
+    assert_eq!(
+        AnnotationQuery::parse("kw:\"light regulation\" os:arabidopsis OR auxin").unwrap(),
+        AnnotationQuery::keyword("light regulation")
+            .and(AnnotationQuery::organism("arabidopsis"))
+            .or(AnnotationQuery::any("auxin"))
+    );
+    assert_eq!(
+        AnnotationQuery::parse("de:box and (sugar or light)").unwrap(),
+        AnnotationQuery::And(vec![
+            AnnotationQuery::description("box"),
+            AnnotationQuery::Or(vec![
+                AnnotationQuery::any("sugar"),
+                AnnotationQuery::any("light")
+            ]),
+        ])
+    );
+
+    for query in ["xx:auxin", "kw:\"light", "(auxin", "auxin OR", ""] {
+        assert!(
+            matches!(AnnotationQuery::parse(query), Err(Error::InvalidQuery(_))),
+            "{}",
+            query
+        );
+    }
+}
+
+#[test]
+fn search_fields() {
+    assert_eq!(search("os:arabidopsis"), [1, 2]);
+    assert_eq!(search("de:GT-1"), [1]);
+    assert_eq!(search("Sugar"), [0, 1, 2]);
+    assert_eq!(search("sugar AND os:arabidopsis"), [1, 2]);
+    assert_eq!(search("de:auxin OR kw:light"), [0, 2]);
+    assert!(
+        index()
+            .search(&AnnotationQuery::Term {
+                field: Field::Organism,
+                text: "tobacco".to_string()
+            })
+            .is_empty()
+    );
+}
+
+#[test]
+fn keyword_terms_match_a_single_keyword() {
+    // "auxin" and "sugar" are two keywords of A, but one of B
+    assert_eq!(search("kw:\"auxin sugar\""), [1]);
+    assert_eq!(search("kw:auxin kw:sugar"), [0, 1]);
+}
+
+#[test]
+fn query_the_embedded_db() {
+    let found = Search::query_elements_by_annotation("kw:\"light regulation\"").unwrap();
+    assert!(found.iter().any(|desc| desc.id == "-10PEHVPSBD"));
+    assert!(
+        found
+            .iter()
+            .all(|desc| desc.kw.iter().any(|kw| kw.contains("light regulation")))
+    );
+}
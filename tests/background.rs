@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use placecare::Error;
+use placecare::background::{
+    Background, BackgroundModel, MarkovModel, dinucleotide_shuffle, expected_counts,
+};
+use placecare::db::PLACE_DB;
+use placecare::filter::ElementFilter;
+use placecare::io::RecordDesc;
+use placecare::place_search::SearchOptions;
+
+fn dinucleotides(seq: &[u8]) -> HashMap<&[u8], usize> {
+    let mut counts = HashMap::new();
+    for pair in seq.windows(2) {
+        *counts.entry(pair).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn wrky_only() -> SearchOptions {
+    SearchOptions {
+        filter: ElementFilter {
+            ids: ["WRKY71OS".to_string()].into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn shuffle_keeps_dinucleotides() {
+    let seq = b"ATGCGCGTTTAACGNNCATGACTGACCGATATTTGCAGG";
+    for seed in 0..20 {
+        let shuffled = dinucleotide_shuffle(seq, seed);
+        assert_eq!(shuffled.len(), seq.len());
+        assert_eq!(shuffled[0], seq[0]);
+        assert_eq!(shuffled[seq.len() - 1], seq[seq.len() - 1]);
+        assert_eq!(dinucleotides(&shuffled), dinucleotides(seq));
+    }
+    assert_ne!(dinucleotide_shuffle(seq, 1), dinucleotide_shuffle(seq, 2));
+    assert_eq!(dinucleotide_shuffle(seq, 7), dinucleotide_shuffle(seq, 7));
+}
+
+#[test]
+fn markov_model_follows_training() {
+    // Only `AC` repeats: an order 1 model almost always alternates
+    let records = vec![RecordDesc::new("r", &"AC".repeat(500))];
+    let model = MarkovModel::train(&records, 1).unwrap();
+    assert_eq!(model.order(), 1);
+
+    let background = Background {
+        model: BackgroundModel::Markov(model),
+        permutations: 3,
+        seed: 5,
+    };
+    let random = background.permutations_of(&records[0], 0);
+    assert_eq!(random.len(), 3);
+    assert_eq!(random[0].id(), "r:perm0");
+    let seq = random[0].seq_bytes();
+    assert_eq!(seq.len(), 1000);
+    let alternating = seq.windows(2).filter(|p| p == b"AC" || p == b"CA").count();
+    assert!(alternating > 950);
+
+    // The order is bounded
+    assert_eq!(MarkovModel::new(8).unwrap().order(), 8);
+    for order in [9, 20] {
+        assert!(matches!(
+            MarkovModel::new(order),
+            Err(Error::InvalidOptions(_))
+        ));
+        assert!(MarkovModel::train(&records, order).is_err());
+        assert!(MarkovModel::train_reader(">r\nACGT\n".as_bytes(), order).is_err());
+    }
+}
+
+#[test]
+fn markov_model_trains_on_a_stream() {
+    let fasta = format!(
+        ">chr1\n{}\n>chr2\nNNNN{}\n",
+        "ACGT".repeat(50),
+        "ACGT".repeat(50)
+    );
+    let from_reader = MarkovModel::train_reader(fasta.as_bytes(), 2).unwrap();
+    let from_records = MarkovModel::train(&RecordDesc::from_string(&fasta).unwrap(), 2).unwrap();
+    assert_eq!(from_reader, from_records);
+}
+
+#[test]
+fn expected_counts_are_reproducible() {
+    let records = RecordDesc::from_string(
+        ">planted\nTGACTGACTGACTGACTGACTGACCCGGGCCCGGGCCCGGGCCCGGGCCCGGG\n\
+         >none\nCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCC\n",
+    )
+    .unwrap();
+    let background = Background {
+        model: BackgroundModel::Markov(MarkovModel::new(0).unwrap()),
+        permutations: 50,
+        seed: 42,
+    };
+    let options = wrky_only();
+
+    let first = expected_counts(&PLACE_DB, &records, &options, &background).unwrap();
+    let second = expected_counts(&PLACE_DB, &records, &options, &background).unwrap();
+    assert_eq!(first.elements, second.elements);
+    assert_eq!(first.to_string(), second.to_string());
+
+    // The planted sequence has 6 TGAC and their reverse complements GTCA
+    assert_eq!(first.elements.len(), 2);
+    let e = &first.elements[0];
+    assert_eq!(
+        (e.query_id.as_str(), e.element_id.as_str()),
+        ("planted", "WRKY71OS")
+    );
+    assert!(e.observed >= 6);
+    assert!(e.expected < e.observed as f64);
+    assert!(e.p_value < 0.05);
+    assert!(e.p_value >= 1.0 / 51.0);
+
+    // The other has none, though its random sequences do
+    let e = &first.elements[1];
+    assert_eq!(
+        (e.query_id.as_str(), e.element_id.as_str()),
+        ("none", "WRKY71OS")
+    );
+    assert_eq!(e.observed, 0);
+    assert!(e.expected > 0.0);
+    assert_eq!(e.p_value, 1.0);
+    assert!(first.to_string().contains("none\tWRKY71OS\t0\t"));
+}
+
+#[test]
+fn shuffles_expect_a_short_element() {
+    // The shuffles keep the TG, GA and AC dinucleotides, so TGAC stays common
+    let records = RecordDesc::from_string(">q\nTGACTGACTGACAAAATTTTGGGGCCCCTGAC\n").unwrap();
+    let background = Background {
+        permutations: 30,
+        ..Default::default()
+    };
+    let result = expected_counts(&PLACE_DB, &records, &wrky_only(), &background).unwrap();
+    let e = &result.elements[0];
+    assert_eq!(result.permutations, 30);
+    assert!(e.expected > 1.0);
+    assert!(result.to_string().starts_with("Query ID\t"));
+}
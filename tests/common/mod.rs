@@ -0,0 +1,23 @@
+//! Fixtures shared by the integration tests.
+
+use placecare::coords::{Coordinates, Strand};
+use placecare::io::SearchedDesc;
+
+/// A hit of `element` on the query `q`, at the 0-based half-open `start..end`.
+pub fn hit(
+    element: &'static str,
+    start: usize,
+    end: usize,
+    strand: Strand,
+) -> SearchedDesc<'static> {
+    SearchedDesc::new(
+        "q",
+        Coordinates::from_zero_based(start, end),
+        strand,
+        element,
+        end - start,
+        "",
+        "S0",
+        "",
+    )
+}
@@ -0,0 +1,229 @@
+use placecare::Error;
+use placecare::coords::{
+    CoordinateSystem, Coordinates, RegionConvention, SourceRegion, Strand, relative_offset,
+    relative_position,
+};
+use placecare::io::{RecordDesc, SearchedDesc};
+use placecare::matcher::reverse_complement;
+use placecare::output::{HitWriter, OutputFormat};
+use placecare::place_search::Search;
+
+// ARFAT (TGTCTC) at 0-based 10..16 on the plus strand,
+// MYBCORE (CNGTTR) at 0-based 6..12 on the minus strand
+const SEQ: &str = "AAACCCTAACTGTCTCAATGGATAAGA";
+
+fn find<'a>(hits: &'a [SearchedDesc<'a>], element: &str) -> &'a SearchedDesc<'a> {
+    hits.iter().find(|hit| hit.e_id == element).unwrap()
+}
+
+#[test]
+fn plus_strand_hit() {
+    let input = vec![RecordDesc::new("q", SEQ)];
+    let result = Search::search_elements(&input).unwrap();
+    let hit = find(&result[0].search_descs, "ARFAT");
+
+    assert_eq!(hit.q_strand, Strand::Plus);
+    assert_eq!(hit.q_coords.zero_based_half_open(), (10, 16));
+    assert_eq!(hit.q_coords.one_based_closed(), (11, 16));
+    assert_eq!(hit.q_coords.len(), hit.e_len);
+    let (start, end) = hit.q_coords.zero_based_half_open();
+    assert_eq!(&SEQ[start..end], "TGTCTC");
+}
+
+#[test]
+fn minus_strand_hit() {
+    let input = vec![RecordDesc::new("q", SEQ)];
+    let result = Search::search_elements(&input).unwrap();
+    let hit = find(&result[0].search_descs, "MYBCORE");
+
+    assert_eq!(hit.q_strand, Strand::Minus);
+    assert_eq!(hit.q_coords.zero_based_half_open(), (6, 12));
+    assert_eq!(hit.q_coords.one_based_closed(), (7, 12));
+    // the forward-strand span holds the reverse complement of the element
+    let (start, end) = hit.q_coords.zero_based_half_open();
+    assert_eq!(&SEQ[start..end], "TAACTG");
+    assert_eq!(reverse_complement("TAACTG"), "CAGTTA");
+}
+
+#[test]
+fn reverse_complemented_query() {
+    let rc = reverse_complement(SEQ);
+    let input = vec![RecordDesc::new("rc", &rc)];
+    let result = Search::search_elements(&input).unwrap();
+    let hit = find(&result[0].search_descs, "ARFAT");
+
+    // 10..16 on a 27 bases sequence is 11..17 on its reverse complement
+    assert_eq!(hit.q_strand, Strand::Minus);
+    assert_eq!(hit.q_coords.zero_based_half_open(), (11, 17));
+    assert_eq!(&rc[11..17], "GAGACA");
+}
+
+#[test]
+fn coordinate_conversions() {
+    let coords = Coordinates::from_one_based(11, 16);
+    assert_eq!(coords, Coordinates::from_zero_based(10, 16));
+    assert_eq!(coords.len(), 6);
+    assert_eq!(
+        coords.in_system(CoordinateSystem::ZeroBasedHalfOpen),
+        (10, 16)
+    );
+    assert_eq!(coords.in_system(CoordinateSystem::OneBasedClosed), (11, 16));
+    assert_eq!(coords.shift(100).zero_based_half_open(), (110, 116));
+
+    // a single base
+    let base = Coordinates::from_zero_based(0, 1);
+    assert_eq!(base.one_based_closed(), (1, 1));
+    assert!(Coordinates::from_zero_based(5, 5).is_empty());
+}
+
+#[test]
+fn writers_declare_their_coordinates() {
+    assert_eq!(
+        OutputFormat::Bed.coordinate_system(),
+        CoordinateSystem::ZeroBasedHalfOpen
+    );
+    assert_eq!(
+        OutputFormat::Gff3.coordinate_system(),
+        CoordinateSystem::OneBasedClosed
+    );
+
+    let input = vec![RecordDesc::new("q", SEQ)];
+    let result = Search::search_elements(&input).unwrap();
+    let hits = &result[0].search_descs;
+
+    let line = |format: OutputFormat, element: &str| {
+        let mut out = HitWriter::new(vec![], format)
+            .unwrap()
+            .with_coordinates(CoordinateSystem::ZeroBasedHalfOpen);
+        out.write(find(hits, element)).unwrap();
+        let text = String::from_utf8(out.finish().unwrap()).unwrap();
+        text.lines().last().unwrap().to_string()
+    };
+
+    assert_eq!(line(OutputFormat::Bed, "ARFAT"), "q\t10\t16\tARFAT\t0\t+");
+    assert_eq!(
+        line(OutputFormat::Bed, "MYBCORE"),
+        "q\t6\t12\tMYBCORE\t0\t-"
+    );
+    // GFF3 stays 1-based closed whatever is asked
+    let gff3 = line(OutputFormat::Gff3, "ARFAT");
+    let columns: Vec<&str> = gff3.split('\t').collect();
+    assert_eq!(columns[3..5], ["11", "16"]);
+    assert_eq!(columns[6], "+");
+    // TSV follows the writer's system
+    let tsv = line(OutputFormat::Tsv, "MYBCORE");
+    let columns: Vec<&str> = tsv.split('\t').collect();
+    assert_eq!(columns[1..4], ["6", "12", "-"]);
+}
+
+#[test]
+fn relative_to_anchor() {
+    // no position 0: the anchor is +1, the base before it -1
+    assert_eq!(relative_position(10, 10), 1);
+    assert_eq!(relative_position(9, 10), -1);
+    assert_eq!(relative_position(0, 10), -10);
+    assert_eq!(relative_offset(0), 1);
+    assert_eq!(relative_offset(-1), -1);
+    assert_eq!(
+        Coordinates::from_zero_based(10, 16).relative_to(27),
+        (-17, -12)
+    );
+
+    // ARFAT ends 12 bases before the end of the sequence
+    let input = RecordDesc::from_string(format!(">q gene=x TSS=28\n{}\n", SEQ)).unwrap();
+    assert_eq!(input[0].anchor(), Some(27));
+    let result = Search::search_elements(&input).unwrap();
+    let hit = find(&result[0].search_descs, "ARFAT");
+    assert_eq!(hit.relative(), Some((-17, -12)));
+
+    let row = hit.row(CoordinateSystem::OneBasedClosed);
+    let columns: Vec<&str> = row.trim_end().split('\t').collect();
+    assert_eq!(columns[10..12], ["-17", "-12"]);
+
+    // without an anchor
+    let input = vec![RecordDesc::new("q", SEQ)];
+    let result = Search::search_elements(&input).unwrap();
+    let hit = find(&result[0].search_descs, "ARFAT");
+    assert_eq!(hit.relative(), None);
+    assert!(
+        hit.row(CoordinateSystem::OneBasedClosed)
+            .ends_with("\t.\t.\t\n")
+    );
+}
+
+#[test]
+fn parse_header_regions() {
+    let samtools = SourceRegion::parse("chr1:1,001-1,100", RegionConvention::Samtools).unwrap();
+    assert_eq!(samtools.contig, "chr1");
+    assert_eq!(samtools.coords.zero_based_half_open(), (1000, 1100));
+    assert_eq!(samtools.strand, Strand::Plus);
+
+    let rc = SourceRegion::parse("chr1:1001-1100/rc", RegionConvention::Samtools).unwrap();
+    assert_eq!(rc.coords, samtools.coords);
+    assert_eq!(rc.strand, Strand::Minus);
+
+    let bedtools =
+        SourceRegion::parse("geneA::chr1:1000-1100(-)", RegionConvention::Bedtools).unwrap();
+    assert_eq!(bedtools.contig, "chr1");
+    assert_eq!(bedtools.coords, samtools.coords);
+    assert_eq!(bedtools.strand, Strand::Minus);
+
+    assert!(SourceRegion::parse("chr1", RegionConvention::Samtools).is_none());
+    assert!(SourceRegion::parse("chr1:0-10", RegionConvention::Samtools).is_none());
+    assert!(SourceRegion::parse("chr1:20-10", RegionConvention::Bedtools).is_none());
+}
+
+#[test]
+fn lift_to_genome() {
+    // SEQ cut from chr1:101-127, then its reverse complement from the same region
+    let fasta = format!(
+        ">q chr1:101-127\n{}\n>rc chr1:101-127(-)\n{}\n",
+        SEQ,
+        reverse_complement(SEQ)
+    );
+    let input: Vec<RecordDesc> = RecordDesc::from_string(fasta)
+        .unwrap()
+        .into_iter()
+        .map(|record| {
+            record
+                .with_header_region(RegionConvention::Samtools)
+                .unwrap()
+        })
+        .collect();
+    let result = Search::search_elements(&input).unwrap();
+
+    // both give ARFAT on the plus strand of chr1 at 0-based 110..116
+    for result in result {
+        let hit = find(&result.search_descs, "ARFAT").clone().lift();
+        assert_eq!(hit.q_id, "chr1");
+        assert_eq!(hit.q_strand, Strand::Plus);
+        assert_eq!(hit.q_coords.zero_based_half_open(), (110, 116));
+        assert!(hit.q_source.is_none());
+    }
+
+    // a region which isn't as long as the sequence
+    let input = RecordDesc::from_string(format!(">q chr1:101-200\n{}\n", SEQ)).unwrap();
+    assert!(
+        input[0]
+            .clone()
+            .with_header_region(RegionConvention::Samtools)
+            .is_err()
+    );
+    let short = SourceRegion::parse("chr1:101-110(-)", RegionConvention::Samtools).unwrap();
+    assert!(matches!(
+        input[0].clone().with_source(short.clone()),
+        Err(Error::InvalidSequence { .. })
+    ));
+
+    // spans past the region aren't lifted
+    assert_eq!(
+        short.lift(Coordinates::from_zero_based(5, 15), Strand::Plus),
+        None
+    );
+    assert_eq!(short.lift_position(10), None);
+    assert_eq!(
+        short.lift(Coordinates::from_zero_based(0, 2), Strand::Plus),
+        Some((Coordinates::from_zero_based(108, 110), Strand::Minus))
+    );
+    assert_eq!(short.lift_position(9), Some(100));
+}
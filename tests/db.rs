@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use placecare::Error;
+use placecare::db::{Database, PLACE_DB};
+use placecare::io::RecordDesc;
+use placecare::parser::PlaceSeqWriter;
+use placecare::place_search::{Search, SearchOptions};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("placecare-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn load_a_place_seq_file() {
+    let descs: Vec<_> = PLACE_DB
+        .seq_desc
+        .all
+        .iter()
+        .filter(|desc| desc.id == "ARFAT" || desc.id == "MYBCORE")
+        .cloned()
+        .collect();
+    let mut writer = PlaceSeqWriter::new(Vec::new());
+    writer.write_all(&descs).unwrap();
+    let path = temp_path("two.seq");
+    std::fs::write(&path, writer.into_inner()).unwrap();
+
+    let db = Database::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(db.seq_desc.all, descs);
+    assert_eq!(db.max_element_len(), 6);
+
+    let records = vec![RecordDesc::new("q", "AAACCCTAACTGTCTCAATGGATAAGA")];
+    let result = Search::search_elements_with(&db, &records, &SearchOptions::default()).unwrap();
+    let mut found: Vec<&str> = result[0].search_descs.iter().map(|hit| hit.e_id).collect();
+    found.sort_unstable();
+    assert_eq!(found, ["ARFAT", "MYBCORE"]);
+}
+
+#[test]
+fn load_a_serialized_db() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/db_file/place.db");
+    let db = Database::from_file(path).unwrap();
+    assert_eq!(db.seq_desc.all, PLACE_DB.seq_desc.all);
+}
+
+#[test]
+fn loading_errors() {
+    let path = temp_path("missing.seq");
+    match Database::from_file(&path) {
+        Err(Error::Io(e)) => assert!(e.to_string().contains("missing.seq")),
+        other => panic!("{:?}", other.map(|_| ())),
+    }
+
+    let path = temp_path("garbage.db");
+    std::fs::write(&path, [0xff; 16]).unwrap();
+    let result = Database::from_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(result, Err(Error::DbDecode(_))));
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::hit;
+use placecare::Error;
+use placecare::coords::{Coordinates, Strand};
+use placecare::db::PLACE_DB;
+use placecare::density::{
+    DensityFormat, DensityGroup, DensityOptions, density, search_density, write_tracks,
+};
+use placecare::io::{RecordDesc, SearchResult};
+use placecare::place_search::SearchOptions;
+
+fn options(window: usize, step: usize, group: DensityGroup) -> DensityOptions {
+    DensityOptions {
+        window,
+        step,
+        group,
+    }
+}
+
+#[test]
+fn sliding_windows() {
+    let spans = |w: Vec<Coordinates>| -> Vec<(usize, usize)> {
+        w.iter().map(|c| c.zero_based_half_open()).collect()
+    };
+    let o = options(10, 4, DensityGroup::All);
+    assert_eq!(spans(o.windows(20)), [(0, 10), (4, 14), (8, 18), (12, 20)]);
+    assert_eq!(spans(o.windows(18)), [(0, 10), (4, 14), (8, 18)]);
+    assert_eq!(spans(o.windows(6)), [(0, 6)]);
+    assert!(o.windows(0).is_empty());
+}
+
+#[test]
+fn hits_count_in_every_window_they_overlap() {
+    let records = vec![RecordDesc::new("q", &"A".repeat(20))];
+    let results = vec![SearchResult::new(
+        "q",
+        vec![
+            hit("E", 9, 13, Strand::Plus),
+            hit("F", 0, 4, Strand::Plus),
+            hit("E", 18, 20, Strand::Plus),
+        ],
+    )];
+
+    let all = density(
+        &PLACE_DB,
+        &records,
+        &results,
+        &options(10, 4, DensityGroup::All),
+    )
+    .unwrap();
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].group, "all");
+    let hits: Vec<usize> = all[0].windows.iter().map(|w| w.hits).collect();
+    // [0, 10) [4, 14) [8, 18) [12, 20)
+    assert_eq!(hits, [2, 1, 1, 2]);
+    assert_eq!(all[0].windows[3].per_kb(), 250.0);
+
+    let by_element = density(
+        &PLACE_DB,
+        &records,
+        &results,
+        &options(10, 4, DensityGroup::Elements),
+    )
+    .unwrap();
+    let groups: Vec<&str> = by_element.iter().map(|t| t.group.as_str()).collect();
+    assert_eq!(groups, ["E", "F"]);
+    let hits: Vec<usize> = by_element[1].windows.iter().map(|w| w.hits).collect();
+    assert_eq!(hits, [1, 0, 0, 0]);
+}
+
+#[test]
+fn writes_the_tracks() {
+    let records = vec![RecordDesc::new("q", &"A".repeat(8))];
+    let results = vec![SearchResult::new("q", vec![hit("E", 1, 3, Strand::Plus)])];
+    let o = options(4, 4, DensityGroup::All);
+    let tracks = density(&PLACE_DB, &records, &results, &o).unwrap();
+
+    let write = |format| {
+        let mut out = vec![];
+        write_tracks(&tracks, format, &o, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+    assert_eq!(
+        write(DensityFormat::Tsv),
+        "Query ID\tGroup\tStart\tEnd\tHits\tHits per kb\n\
+         q\tall\t1\t4\t1\t250.000\n\
+         q\tall\t5\t8\t0\t0.000\n"
+    );
+    assert_eq!(
+        write(DensityFormat::BedGraph),
+        "track type=bedGraph name=\"all\" description=\"all hits per kb\"\n\
+         q\t0\t4\t250.000\n\
+         q\t4\t8\t0.000\n"
+    );
+    assert_eq!(
+        write(DensityFormat::Wig),
+        "track type=wiggle_0 name=\"all\" description=\"all hits per kb\"\n\
+         fixedStep chrom=q start=1 step=4 span=4\n\
+         250.000\n\
+         0.000\n"
+    );
+}
+
+#[test]
+fn bedgraph_intervals_do_not_overlap() {
+    let records = vec![RecordDesc::new("q", &"A".repeat(20))];
+    let results = vec![SearchResult::new("q", vec![hit("E", 9, 13, Strand::Plus)])];
+    let o = options(10, 4, DensityGroup::All);
+    let tracks = density(&PLACE_DB, &records, &results, &o).unwrap();
+
+    let mut out = vec![];
+    write_tracks(&tracks, DensityFormat::BedGraph, &o, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let intervals: Vec<(usize, usize)> = out
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            (fields[1].parse().unwrap(), fields[2].parse().unwrap())
+        })
+        .collect();
+    // The windows [0, 10) [4, 14) [8, 18) [12, 20), each up to the next one
+    assert_eq!(intervals, [(0, 4), (4, 8), (8, 12), (12, 20)]);
+    assert!(intervals.windows(2).all(|pair| pair[0].1 <= pair[1].0));
+}
+
+#[test]
+fn keyword_tracks_of_a_search() {
+    let record = RecordDesc::new("q", "GGGGGGGGGGCCAATGGGGGGGGGGGGGGGGGG");
+    let tracks = search_density(
+        &PLACE_DB,
+        &record,
+        &SearchOptions::default(),
+        &options(10, 10, DensityGroup::Keywords),
+    )
+    .unwrap();
+    let caat = tracks.iter().find(|t| t.group == "CAAT").unwrap();
+    let hits: Vec<usize> = caat.windows.iter().map(|w| w.hits).collect();
+    assert_eq!(hits, [0, 1, 0, 0]);
+}
+
+#[test]
+fn rejects_empty_windows() {
+    let result = density(&PLACE_DB, &[], &[], &options(0, 10, DensityGroup::All));
+    assert!(matches!(result, Err(Error::InvalidOptions(_))));
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::hit;
+use placecare::coords::Strand;
+use placecare::enrichment::{EnrichmentTest, enrich};
+use placecare::io::SearchResult;
+
+/// `total` sequences, the first `with[i].1` of them hit once by element `with[i].0`.
+fn results(
+    prefix: &str,
+    total: usize,
+    with: &[(&'static str, usize)],
+) -> Vec<SearchResult<'static>> {
+    (0..total)
+        .map(|i| {
+            let hits = with
+                .iter()
+                .filter(|&&(_, n)| i < n)
+                .map(|&(element, _)| hit(element, 0, 4, Strand::Plus))
+                .collect();
+            SearchResult::new(&format!("{}{}", prefix, i), hits)
+        })
+        .collect()
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * b.abs().max(1e-300)
+}
+
+#[test]
+fn fisher_exact_test() {
+    // [[5, 15], [10, 170]]
+    let fg = results("fg", 20, &[("E", 5), ("F", 1)]);
+    let bg = results("bg", 180, &[("E", 10), ("F", 90)]);
+    let result = enrich(&fg, &bg, EnrichmentTest::Fisher);
+
+    assert_eq!((result.fg_total, result.bg_total), (20, 180));
+    let e = &result.elements[0];
+    assert_eq!(e.element_id, "E");
+    assert_eq!((e.fg_seqs, e.bg_seqs), (5, 10));
+    assert!(close(e.fold, (5.0 / 20.0) / (10.0 / 180.0)));
+    assert!(close(e.p_value, 0.009399732308132432));
+
+    // F is depleted
+    let f = &result.elements[1];
+    assert!(f.p_value > 0.99);
+    assert!(f.fold < 1.0);
+    // q = p * 2 / rank, made monotonic
+    assert!(close(e.q_value, e.p_value * 2.0));
+    assert!(close(f.q_value, f.p_value.min(1.0)));
+}
+
+#[test]
+fn hypergeometric_test() {
+    // all 20 foreground sequences among the 54 of the 200 background ones
+    let fg = results("g", 20, &[("E", 20)]);
+    let bg = results("g", 200, &[("E", 54)]);
+    let result = enrich(&fg, &bg, EnrichmentTest::Hypergeometric);
+
+    let e = &result.elements[0];
+    assert!(close(e.p_value, 1.9917563130819137e-13));
+    assert!(close(e.fold, 200.0 / 54.0));
+}
+
+#[test]
+fn counts_sequences_once() {
+    let mut fg = results("fg", 2, &[("E", 1)]);
+    let extra = fg[0].search_descs[0].clone();
+    fg[0].search_descs.push(extra);
+    let bg = results("bg", 2, &[]);
+    let result = enrich(&fg, &bg, EnrichmentTest::Fisher);
+
+    let e = &result.elements[0];
+    assert_eq!((e.fg_hits, e.fg_seqs), (2, 1));
+    assert_eq!((e.bg_hits, e.bg_seqs), (0, 0));
+    assert!(e.fold.is_infinite());
+    assert!(result.to_string().starts_with("Element ID\t"));
+}
@@ -0,0 +1,45 @@
+use placecare::Error;
+use placecare::io::RecordDesc;
+use placecare::place_search::{Search, SearchOptions};
+
+#[test]
+fn input_errors() {
+    match RecordDesc::from_file("missing-input.fasta") {
+        Err(Error::Io(e)) => assert!(e.to_string().starts_with("missing-input.fasta: ")),
+        other => panic!("{:?}", other),
+    }
+    assert!(matches!(
+        RecordDesc::from_string("ACGT\n"),
+        Err(Error::Fasta(_))
+    ));
+
+    let bytes: &[u8] = b">q\nAC\xffGT\n";
+    assert!(matches!(
+        RecordDesc::from_reader(bytes),
+        Err(Error::Fasta(_))
+    ));
+}
+
+#[test]
+fn search_errors() {
+    let records = vec![RecordDesc::new("q", "ACGT")];
+    let mut options = SearchOptions::default();
+    options.filter.ids = ["NOTANELEMENT".to_string()].into();
+    match Search::search_elements_with(&placecare::db::PLACE_DB, &records, &options) {
+        Err(Error::UnknownElement(name)) => assert_eq!(name, "NOTANELEMENT"),
+        other => panic!("{:?}", other.map(|_| ())),
+    }
+
+    // Excluding a missing element is harmless
+    let mut options = SearchOptions::default();
+    options.filter.exclude = ["NOTANELEMENT".to_string(), "ARFAT".to_string()].into();
+    assert!(Search::search_elements_with(&placecare::db::PLACE_DB, &records, &options).is_ok());
+    assert_eq!(
+        options.filter.unknown_exclusions(&placecare::db::PLACE_DB),
+        ["NOTANELEMENT"]
+    );
+    assert!(matches!(
+        Search::query_elements_by_annotation("kw:(auxin"),
+        Err(Error::InvalidQuery(_))
+    ));
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::hit;
+use placecare::coords::Strand;
+use placecare::db::PLACE_DB;
+use placecare::io::{RecordDesc, SearchResult};
+use placecare::matrix::{HitMatrix, MatrixColumns, MatrixValue};
+use placecare::place_search::Search;
+
+fn results() -> Vec<SearchResult<'static>> {
+    vec![
+        SearchResult::new(
+            "g1",
+            vec![
+                hit("WRKY71OS", 0, 4, Strand::Plus),
+                hit("WRKY71OS", 0, 4, Strand::Minus),
+                hit("WRKY71OS", 0, 4, Strand::Plus),
+            ],
+        ),
+        SearchResult::new("g2", vec![]),
+        SearchResult::new("g3", vec![hit("ARR1AT", 0, 4, Strand::Minus)]),
+    ]
+}
+
+fn tsv(matrix: &HitMatrix) -> String {
+    let mut out = vec![];
+    matrix.write_tsv(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn counts_and_presence() {
+    let counts = HitMatrix::from_results(
+        &PLACE_DB,
+        &results(),
+        MatrixColumns::Elements,
+        MatrixValue::Count,
+        false,
+    );
+    assert_eq!(counts.rows, ["g1", "g2", "g3"]);
+    assert_eq!(counts.columns, ["ARR1AT", "WRKY71OS"]);
+    assert_eq!(counts.cells, [(0, 1, 3), (2, 0, 1)]);
+    assert_eq!(counts.get(0, 1), 3);
+    assert_eq!(counts.get(1, 1), 0);
+    assert_eq!(
+        tsv(&counts),
+        "Query ID\tARR1AT\tWRKY71OS\ng1\t0\t3\ng2\t0\t0\ng3\t1\t0\n"
+    );
+
+    let presence = HitMatrix::from_results(
+        &PLACE_DB,
+        &results(),
+        MatrixColumns::Elements,
+        MatrixValue::Presence,
+        false,
+    );
+    assert_eq!(presence.cells, [(0, 1, 1), (2, 0, 1)]);
+}
+
+#[test]
+fn strand_split_columns() {
+    let matrix = HitMatrix::from_results(
+        &PLACE_DB,
+        &results(),
+        MatrixColumns::Elements,
+        MatrixValue::Strand,
+        false,
+    );
+    assert_eq!(matrix.columns, ["ARR1AT(-)", "WRKY71OS(+)", "WRKY71OS(-)"]);
+    assert_eq!(matrix.cells, [(0, 1, 2), (0, 2, 1), (2, 0, 1)]);
+
+    let all = HitMatrix::from_results(
+        &PLACE_DB,
+        &results(),
+        MatrixColumns::Elements,
+        MatrixValue::Strand,
+        true,
+    );
+    assert_eq!(
+        all.columns.len(),
+        PLACE_DB.place_db().seq_desc.all.len() * 2
+    );
+    let wrky = all.columns.iter().position(|c| c == "WRKY71OS(+)").unwrap();
+    assert_eq!(all.get(0, wrky), 2);
+}
+
+#[test]
+fn matrix_market() {
+    let matrix = HitMatrix::from_results(
+        &PLACE_DB,
+        &results(),
+        MatrixColumns::Elements,
+        MatrixValue::Count,
+        false,
+    );
+    let mut out = vec![];
+    matrix.write_matrix_market(&mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "%%MatrixMarket matrix coordinate integer general\n3 2 2\n1 2 3\n3 1 1\n"
+    );
+
+    let mut names = vec![];
+    HitMatrix::write_names(&matrix.columns, &mut names).unwrap();
+    assert_eq!(String::from_utf8(names).unwrap(), "ARR1AT\nWRKY71OS\n");
+}
+
+#[test]
+fn keyword_columns() {
+    let input = RecordDesc::from_string(">g1\nTGACTGACTATAAATCCAAT\n>g2\nCCCCCCCC\n").unwrap();
+    let results = Search::search_elements(&input).unwrap();
+    let matrix = HitMatrix::from_results(
+        &PLACE_DB,
+        &results,
+        MatrixColumns::Keywords,
+        MatrixValue::Presence,
+        false,
+    );
+
+    let caat = matrix.columns.iter().position(|c| c == "CAAT").unwrap();
+    assert_eq!(matrix.get(0, caat), 1);
+    assert_eq!(matrix.get(1, caat), 0);
+    assert!(!matrix.columns.iter().any(|c| c == "CAATBOX1"));
+}
@@ -0,0 +1,159 @@
+use placecare::coords::CoordinateSystem;
+use placecare::db::PLACE_DB;
+use placecare::io::{OwnedSearchResult, OwnedSearchedDesc, RecordDesc, SearchResult};
+use placecare::output::{HitWriter, OutputFormat};
+use placecare::place_search::{Search, SearchOptions};
+
+// ARFAT (TGTCTC) at 0-based 10..16 on the plus strand,
+// MYBCORE (CNGTTR) at 0-based 6..12 on the minus strand
+const SEQ: &str = "AAACCCTAACTGTCTCAATGGATAAGA";
+
+fn written(results: &[SearchResult], format: OutputFormat) -> String {
+    let mut writer = HitWriter::new(vec![], format).unwrap();
+    writer.write_all(results).unwrap();
+    String::from_utf8(writer.finish().unwrap()).unwrap()
+}
+
+fn line<'a>(text: &'a str, element: &str) -> Vec<&'a str> {
+    text.lines()
+        .find(|line| line.split('\t').any(|column| column == element))
+        .unwrap()
+        .split('\t')
+        .collect()
+}
+
+#[test]
+fn bed_and_gff3() {
+    let records = vec![RecordDesc::new("q", SEQ)];
+    let results = Search::search_elements(&records).unwrap();
+
+    let bed = written(&results, OutputFormat::Bed);
+    assert_eq!(bed.lines().count(), results[0].search_descs.len());
+    assert_eq!(line(&bed, "ARFAT"), ["q", "10", "16", "ARFAT", "0", "+"]);
+    assert_eq!(line(&bed, "MYBCORE"), ["q", "6", "12", "MYBCORE", "0", "-"]);
+
+    let gff3 = written(&results, OutputFormat::Gff3);
+    assert!(gff3.starts_with("##gff-version 3\n"));
+    let arfat: Vec<&str> = gff3
+        .lines()
+        .find(|line| line.contains("Name=ARFAT;"))
+        .unwrap()
+        .split('\t')
+        .collect();
+    // The same hit, 1-based
+    assert_eq!(
+        arfat[..8],
+        [
+            "q",
+            "placecare",
+            "transcriptional_cis_regulatory_region",
+            "11",
+            "16",
+            ".",
+            "+",
+            "."
+        ]
+    );
+    assert!(arfat[8].starts_with("Name=ARFAT;Dbxref=PLACE:S000"));
+    assert!(arfat[8].ends_with(";mismatches=0"));
+    // Reserved characters are escaped
+    assert!(
+        !arfat[8]
+            .split(';')
+            .any(|attribute| attribute.matches('=').count() != 1)
+    );
+}
+
+#[test]
+fn gff3_mismatches_and_anchor() {
+    // ARFAT with its 5th base changed, the TSS on the last base
+    let records = vec![RecordDesc::new("q", "GGGGGTGTCACGGGGG").with_anchor(15)];
+    let options = SearchOptions {
+        max_mismatches: 1,
+        ..Default::default()
+    };
+    let results = Search::search_elements_with(&PLACE_DB, &records, &options)
+        .unwrap()
+        .into_iter()
+        .map(|mut result| {
+            result.search_descs.retain(|hit| hit.e_id == "ARFAT");
+            result
+        })
+        .collect::<Vec<_>>();
+
+    let gff3 = written(&results, OutputFormat::Gff3);
+    let attributes = gff3.lines().nth(1).unwrap().split('\t').nth(8).unwrap();
+    assert!(
+        attributes
+            .ends_with(";mismatches=1;mismatch_positions=10;relative_start=-10;relative_end=-5")
+    );
+}
+
+#[test]
+fn json_and_json_lines() {
+    let records = vec![RecordDesc::new("q", SEQ).with_anchor(27)];
+    let results = Search::search_elements(&records).unwrap();
+    let expected: Vec<OwnedSearchedDesc> = results[0]
+        .search_descs
+        .iter()
+        .map(|hit| hit.to_owned_desc(CoordinateSystem::OneBasedClosed))
+        .collect();
+
+    let json = written(&results, OutputFormat::Json);
+    let hits: Vec<OwnedSearchedDesc> = serde_json::from_str(&json).unwrap();
+    assert_eq!(hits, expected);
+    let arfat = hits.iter().find(|hit| hit.element_id == "ARFAT").unwrap();
+    assert_eq!((arfat.start, arfat.end), (11, 16));
+    assert_eq!(
+        (arfat.relative_start, arfat.relative_end),
+        (Some(-17), Some(-12))
+    );
+    assert!(arfat.keywords.is_none());
+
+    let lines = written(&results, OutputFormat::JsonLines);
+    let hits: Vec<OwnedSearchedDesc> = lines
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(hits, expected);
+
+    assert_eq!(written(&[], OutputFormat::Json), "[]\n");
+    assert_eq!(written(&[], OutputFormat::JsonLines), "");
+}
+
+#[test]
+fn json_metadata_and_coordinates() {
+    let records = vec![RecordDesc::new("q", SEQ)];
+    let results = Search::search_elements(&records).unwrap();
+    let mut writer = HitWriter::new(vec![], OutputFormat::JsonLines)
+        .unwrap()
+        .with_metadata(&PLACE_DB)
+        .with_coordinates(CoordinateSystem::ZeroBasedHalfOpen);
+    writer.write_all(&results).unwrap();
+    let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+    let arfat: OwnedSearchedDesc = text
+        .lines()
+        .map(|line| serde_json::from_str::<OwnedSearchedDesc>(line).unwrap())
+        .find(|hit| hit.element_id == "ARFAT")
+        .unwrap();
+    assert_eq!((arfat.start, arfat.end), (10, 16));
+    assert_eq!(arfat.coordinates, CoordinateSystem::ZeroBasedHalfOpen);
+    assert!(
+        arfat
+            .keywords
+            .unwrap()
+            .iter()
+            .any(|kw| kw.contains("auxin"))
+    );
+    assert!(arfat.organism.is_some());
+
+    // The owned results outlive the query
+    let owned = results[0].to_owned_result(CoordinateSystem::OneBasedClosed);
+    drop(results);
+    drop(records);
+    let round_trip: OwnedSearchResult =
+        serde_json::from_str(&serde_json::to_string(&owned).unwrap()).unwrap();
+    assert_eq!(round_trip, owned);
+    assert_eq!(owned.count, owned.hits.len());
+}
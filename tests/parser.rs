@@ -0,0 +1,85 @@
+use placecare::db::PLACE_DB;
+use placecare::parser::{ParseError, PlaceSeqReader, PlaceSeqWriter, parse_place_seq};
+
+const PLACE_SEQ: &str = include_str!("../db_place/place.seq");
+
+#[test]
+fn parse_bundled_place_seq() {
+    let descs = parse_place_seq(PLACE_SEQ).unwrap();
+    assert_eq!(descs.len(), 469);
+
+    let first = &descs[0];
+    assert_eq!(first.id, "-10PEHVPSBD");
+    assert_eq!(first.ac, "S000392");
+    assert_eq!(first.os, "barley (Hordeum vulgare)");
+    assert_eq!(first.sq, "TATTCT");
+    assert!(
+        first
+            .de
+            .starts_with("\"-10 promoter element\" found in the barley")
+    );
+    // keywords split over two KW lines
+    assert!(first.kw.contains(&"light regulation".to_string()));
+    assert_eq!(first.refs.len(), 1);
+    assert_eq!(first.refs[0].pubmed, ["11587507"]);
+}
+
+#[test]
+fn parse_several_references() {
+    let descs = parse_place_seq(PLACE_SEQ).unwrap();
+    let desc = descs.iter().find(|d| d.id == "-141NTG13").unwrap();
+
+    assert_eq!(desc.refs.len(), 2);
+    assert_eq!(desc.refs[0].authors, "Fromm H, Katagiri F, Chua NH");
+    assert_eq!(desc.refs[0].location, "Mol Gen Genet 229: 181-188 (1991)");
+    assert_eq!(desc.refs[0].pubmed, ["1921969"]);
+    assert_eq!(desc.refs[0].genbank, ["M62855"]);
+    assert_eq!(desc.refs[1].pubmed, ["10809441"]);
+    assert!(desc.refs[1].genbank.is_empty());
+    assert!(
+        desc.refs[1]
+            .title
+            .starts_with("A xenobiotic-stress-activated")
+    );
+}
+
+#[test]
+fn embedded_db_matches_bundled_place_seq() {
+    let descs = parse_place_seq(PLACE_SEQ).unwrap();
+    assert_eq!(PLACE_DB.seq_desc.all, descs);
+}
+
+#[test]
+fn write_round_trip() {
+    let descs = parse_place_seq(PLACE_SEQ).unwrap();
+
+    let mut writer = PlaceSeqWriter::new(Vec::new());
+    writer.write_all(&descs).unwrap();
+    let written = String::from_utf8(writer.into_inner()).unwrap();
+
+    assert!(written.lines().all(|line| line.len() <= 72));
+    assert_eq!(parse_place_seq(&written).unwrap(), descs);
+}
+
+#[test]
+fn stream_entries_one_by_one() {
+    let mut reader = PlaceSeqReader::new(PLACE_SEQ.as_bytes());
+    assert_eq!(reader.next().unwrap().unwrap().id, "-10PEHVPSBD");
+    assert_eq!(reader.next().unwrap().unwrap().id, "-141NTG13");
+    assert_eq!(reader.count(), 467);
+}
+
+#[test]
+fn report_invalid_lines() {
+    let text = "ID   A\nXX\nZZ   what\nSQ\n     ACGT\n//\n";
+    match parse_place_seq(text) {
+        Err(ParseError::Format { line, .. }) => assert_eq!(line, 3),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let text = "ID   A\nXX\nAC   S1\n//\n";
+    assert!(matches!(
+        parse_place_seq(text),
+        Err(ParseError::Format { line: 4, .. })
+    ));
+}
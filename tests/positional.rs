@@ -0,0 +1,131 @@
+mod common;
+
+use common::hit;
+use placecare::coords::Strand;
+use placecare::io::{RecordDesc, SearchResult};
+use placecare::place_search::Search;
+use placecare::positional::{PositionalOptions, positional};
+
+fn options(bin: usize, near: usize) -> PositionalOptions {
+    PositionalOptions {
+        bin,
+        near,
+        min_hits: 1,
+    }
+}
+
+#[test]
+fn bins_around_the_anchor() {
+    // 100 bases upstream of the TSS, 20 downstream
+    let records = vec![RecordDesc::new("p", &"A".repeat(120)).with_anchor(100)];
+    let results = vec![SearchResult::new(
+        "p",
+        vec![
+            hit("E", 70, 74, Strand::Plus),
+            hit("E", 72, 76, Strand::Minus),
+            hit("E", 0, 4, Strand::Plus),
+            hit("E", 105, 109, Strand::Plus),
+        ],
+    )];
+    let result = positional(&records, &results, &options(25, 10));
+
+    assert_eq!(result.queries, 1);
+    assert_eq!(result.first, -100);
+    assert_eq!(result.coverage, [25, 25, 25, 25, 20]);
+    assert_eq!(result.bin_range(0), (-100, -76));
+    assert_eq!(result.bin_range(3), (-25, -1));
+    assert_eq!(result.bin_range(4), (1, 25));
+
+    let e = &result.elements[0];
+    // Centres at -29, -27, -99 and +7
+    assert_eq!(e.plus, [1, 0, 1, 0, 1]);
+    assert_eq!(e.minus, [0, 0, 1, 0, 0]);
+    assert_eq!(e.peak, 2);
+    assert_eq!((e.hits, e.near_hits), (4, 1));
+    assert!((e.expected_near - 4.0 * 20.0 / 120.0).abs() < 1e-12);
+}
+
+#[test]
+fn flags_elements_near_the_tss() {
+    // 40 promoters of 200 bases, the TSS at their end
+    let records: Vec<RecordDesc> = (0..40)
+        .map(|i| RecordDesc::new(&format!("p{}", i), &"A".repeat(200)).with_anchor(200))
+        .collect();
+    let results: Vec<SearchResult> = (0..40)
+        .map(|i| {
+            SearchResult::new(
+                &format!("p{}", i),
+                vec![
+                    hit("NEAR", 170, 174, Strand::Plus),
+                    hit("SPREAD", i * 5, i * 5 + 4, Strand::Plus),
+                ],
+            )
+        })
+        .collect();
+    let result = positional(&records, &results, &options(50, 50));
+
+    let near = &result.elements[0];
+    assert_eq!(near.element_id, "NEAR");
+    assert_eq!(near.near_hits, 40);
+    // (1/4)^40
+    assert!((near.p_value - 0.25f64.powi(40)).abs() < 1e-30);
+    assert!(near.fold > 3.9);
+    assert_eq!(result.bin_range(near.peak), (-50, -1));
+
+    let spread = &result.elements[1];
+    assert_eq!(spread.element_id, "SPREAD");
+    assert!(spread.p_value > 0.3);
+    assert!(spread.q_value >= spread.p_value);
+}
+
+#[test]
+fn skips_queries_without_an_anchor() {
+    let records = vec![
+        RecordDesc::new("a", "ACGTACGT").with_anchor(4),
+        RecordDesc::new("b", "ACGTACGT"),
+    ];
+    let results = vec![
+        SearchResult::new("a", vec![hit("E", 0, 4, Strand::Plus)]),
+        SearchResult::new("b", vec![hit("E", 0, 4, Strand::Plus)]),
+    ];
+    let result = positional(&records, &results, &options(4, 2));
+    assert_eq!(result.queries, 1);
+    assert_eq!(result.elements[0].hits, 1);
+
+    let none = positional(&records[1..], &results[1..], &options(4, 2));
+    assert_eq!(none.queries, 0);
+    assert!(none.elements.is_empty());
+}
+
+#[test]
+fn writes_tables_and_plot() {
+    let records =
+        RecordDesc::from_string(">p1 tss=41\nGGGGGGGGGGGGGGGGGGGGGGGGGGGGGTATAAATGGGGGGG\n")
+            .unwrap();
+    let results = Search::search_elements(&records).unwrap();
+    let result = positional(&records, &results, &options(10, 20));
+
+    assert!(
+        result
+            .to_string()
+            .starts_with("Element ID\tHits\tNear Hits\t")
+    );
+    let tata = result
+        .elements
+        .iter()
+        .find(|e| e.element_id == "TATABOX2")
+        .unwrap();
+    assert_eq!(result.bin_range(tata.peak), (-10, -1));
+
+    let mut histograms = vec![];
+    result.write_histograms(&mut histograms).unwrap();
+    let histograms = String::from_utf8(histograms).unwrap();
+    assert!(histograms.contains("TATABOX2\t-10\t-1\t1\t0\t1\t"));
+
+    let mut svg = vec![];
+    result.write_svg(&mut svg, 3).unwrap();
+    let svg = String::from_utf8(svg).unwrap();
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert!(svg.matches("<polyline").count() <= 3);
+}
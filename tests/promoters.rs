@@ -0,0 +1,157 @@
+use placecare::coords::Strand;
+use placecare::io::RecordDesc;
+use placecare::promoters::{self, Annotation, AnnotationFormat, FeatureLevel, PromoterOptions};
+
+// 40 bases, chr1:1-10 is AAAAACCCCC
+const GENOME: &str = ">chr1 test\nAAAAACCCCCGGGGGTTTTTACGTACGTACTTTTTGGGGG\n>chr2\nACGT\n";
+
+const GFF3: &str = "\
+##gff-version 3
+chr1\t.\tgene\t11\t15\t.\t+\t.\tID=geneA;Name=A
+chr1\t.\tmRNA\t11\t15\t.\t+\t.\tID=geneA.1;Parent=geneA
+chr1\t.\tgene\t16\t20\t.\t-\t.\tID=geneB
+chr1\t.\tgene\t31\t38\t.\t-\t.\tID=gene%3BC
+chr1\t.\tgene\t21\t25\t.\t.\t.\tID=unstranded
+##FASTA
+>chr1
+ACGT
+";
+
+const GTF: &str = "\
+chr1\t.\texon\t11\t12\t.\t+\t.\tgene_id \"geneA\"; transcript_id \"geneA.1\";
+chr1\t.\texon\t14\t15\t.\t+\t.\tgene_id \"geneA\"; transcript_id \"geneA.2\";
+chr1\t.\texon\t16\t20\t.\t-\t.\tgene_id \"geneB\"; transcript_id \"geneB.1\";
+";
+
+fn genome() -> Vec<RecordDesc> {
+    RecordDesc::from_string(GENOME).unwrap()
+}
+
+fn gff3(level: FeatureLevel) -> Annotation {
+    Annotation::from_reader(GFF3.as_bytes(), AnnotationFormat::Gff3, level).unwrap()
+}
+
+#[test]
+fn read_gff3_features() {
+    let genes = gff3(FeatureLevel::Gene);
+    let ids: Vec<&str> = genes.features.iter().map(|f| f.id.as_str()).collect();
+    assert_eq!(ids, ["geneA", "geneB", "gene;C"]);
+    assert_eq!(genes.features[1].strand, Strand::Minus);
+    assert_eq!(genes.features[1].tss(), 19);
+
+    let transcripts = gff3(FeatureLevel::Transcript);
+    assert_eq!(transcripts.features.len(), 1);
+    assert_eq!(transcripts.features[0].id, "geneA.1");
+}
+
+#[test]
+fn read_gtf_spans() {
+    let genes =
+        Annotation::from_reader(GTF.as_bytes(), AnnotationFormat::Gtf, FeatureLevel::Gene).unwrap();
+    assert_eq!(genes.features.len(), 2);
+    assert_eq!(genes.features[0].coords.one_based_closed(), (11, 15));
+
+    let transcripts = Annotation::from_reader(
+        GTF.as_bytes(),
+        AnnotationFormat::Gtf,
+        FeatureLevel::Transcript,
+    )
+    .unwrap();
+    assert_eq!(transcripts.features.len(), 3);
+    assert_eq!(transcripts.features[1].tss(), 13);
+}
+
+#[test]
+fn strand_aware_promoters() {
+    let options = PromoterOptions {
+        upstream: 5,
+        downstream: 2,
+        stop_at_neighbor: false,
+    };
+    let found = promoters::extract(&genome(), &gff3(FeatureLevel::Gene), &options).unwrap();
+
+    // plus strand: 5 bases before the TSS and 2 from it
+    assert_eq!(found[0].coords.zero_based_half_open(), (5, 12));
+    assert_eq!(found[0].record.seq(), "CCCCCGG");
+    assert_eq!(found[0].tss_offset(), 5);
+    assert_eq!(found[0].location(), "chr1:6-12(+)");
+
+    // minus strand: reverse-complemented, reads from 5 bases upstream to the TSS + 1
+    assert_eq!(found[1].coords.zero_based_half_open(), (18, 25));
+    assert_eq!(found[1].record.seq(), "TACGTAA");
+    assert_eq!(found[1].tss_offset(), 5);
+    assert_eq!(found[1].location(), "chr1:19-25(-)");
+
+    // the records are anchored at the TSS
+    assert_eq!(found[0].record.anchor(), Some(5));
+    assert_eq!(found[1].record.anchor(), Some(5));
+}
+
+#[test]
+fn clip_at_sequence_ends() {
+    let options = PromoterOptions {
+        upstream: 100,
+        ..Default::default()
+    };
+    let found = promoters::extract(&genome(), &gff3(FeatureLevel::Gene), &options).unwrap();
+
+    assert_eq!(found[0].coords.zero_based_half_open(), (0, 10));
+    assert_eq!(found[2].coords.zero_based_half_open(), (38, 40));
+    assert_eq!(found[2].record.seq(), "CC");
+    // the TSS is right after the upstream region
+    assert_eq!(found[2].record.anchor(), Some(2));
+}
+
+#[test]
+fn stop_at_neighbor() {
+    let options = PromoterOptions {
+        upstream: 100,
+        downstream: 0,
+        stop_at_neighbor: true,
+    };
+    let found = promoters::extract(&genome(), &gff3(FeatureLevel::Gene), &options).unwrap();
+
+    // geneA has no gene before it, geneB's upstream region runs into gene;C
+    assert_eq!(found[0].coords.zero_based_half_open(), (0, 10));
+    assert_eq!(found[1].coords.zero_based_half_open(), (20, 30));
+    assert_eq!(found[2].coords.zero_based_half_open(), (38, 40));
+}
+
+#[test]
+fn transcripts_stop_at_other_genes_only() {
+    let options = PromoterOptions {
+        upstream: 100,
+        downstream: 0,
+        stop_at_neighbor: true,
+    };
+    let transcripts = Annotation::from_reader(
+        GTF.as_bytes(),
+        AnnotationFormat::Gtf,
+        FeatureLevel::Transcript,
+    )
+    .unwrap();
+    assert_eq!(transcripts.features[1].gene, "geneA");
+    let found = promoters::extract(&genome(), &transcripts, &options).unwrap();
+
+    // geneA.2 starts after geneA.1 ends, their promoters both reach the sequence start
+    assert_eq!(found[0].id, "geneA.1");
+    assert_eq!(found[0].coords.zero_based_half_open(), (0, 10));
+    assert_eq!(found[1].id, "geneA.2");
+    assert_eq!(found[1].coords.zero_based_half_open(), (0, 13));
+    assert_eq!(found[1].record.anchor(), Some(13));
+    // geneB.1 still stops at nothing but the sequence end
+    assert_eq!(found[2].coords.zero_based_half_open(), (20, 40));
+
+    // In GFF3 the gene of a transcript is its parent
+    assert_eq!(gff3(FeatureLevel::Transcript).features[0].gene, "geneA");
+    assert_eq!(gff3(FeatureLevel::Gene).features[0].gene, "geneA");
+}
+
+#[test]
+fn unknown_sequence() {
+    let gff3 = "chr9\t.\tgene\t1\t2\t.\t+\t.\tID=x\n";
+    let annotation =
+        Annotation::from_reader(gff3.as_bytes(), AnnotationFormat::Gff3, FeatureLevel::Gene)
+            .unwrap();
+    assert!(promoters::extract(&genome(), &annotation, &PromoterOptions::default()).is_err());
+}
@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+
+use placecare::coords::Strand;
+use placecare::db::PLACE_DB;
+use placecare::io::{RecordDesc, SearchResult};
+use placecare::matcher::{AmbiguityPolicy, reverse_complement, reverse_complement_bytes};
+use placecare::place_search::{Search, SearchOptions};
+
+/// A reproducible A/C/G/T sequence.
+fn random_seq(len: usize, mut seed: u64) -> String {
+    (0..len)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            b"ACGT"[(seed >> 62) as usize] as char
+        })
+        .collect()
+}
+
+/// The exact elements found by scanning the query and its reverse complement
+/// for every element, one by one, like the KMP search did.
+fn naive_exact_hits(seq: &str) -> Vec<(String, usize, usize, char)> {
+    let rc = reverse_complement(seq);
+    let len = seq.len();
+    let mut hits = vec![];
+    for desc in &PLACE_DB.place_db().seq_desc.exact {
+        let m = desc.sq.len();
+        for s in 0..=len.saturating_sub(m) {
+            if m <= len && seq[s..s + m] == desc.sq {
+                hits.push((desc.id.clone(), s, s + m, '+'));
+            }
+            if m <= len && rc[s..s + m] == desc.sq {
+                hits.push((desc.id.clone(), len - s - m, len - s, '-'));
+            }
+        }
+    }
+    hits.sort();
+    hits
+}
+
+fn exact_hits(records: &[RecordDesc]) -> Vec<Vec<(String, usize, usize, char)>> {
+    let exact: HashSet<&str> = PLACE_DB
+        .place_db()
+        .seq_desc
+        .exact
+        .iter()
+        .map(|desc| desc.id.as_str())
+        .collect();
+    Search::search_elements(records)
+        .unwrap()
+        .iter()
+        .map(|result| {
+            let mut hits: Vec<_> = result
+                .search_descs
+                .iter()
+                .filter(|hit| exact.contains(hit.e_id))
+                .map(|hit| {
+                    let (start, end) = hit.q_coords.zero_based_half_open();
+                    (hit.e_id.to_string(), start, end, hit.q_strand.as_char())
+                })
+                .collect();
+            hits.sort();
+            hits
+        })
+        .collect()
+}
+
+#[test]
+fn automaton_matches_a_naive_scan() {
+    let records: Vec<RecordDesc> = (0..4)
+        .map(|i| RecordDesc::new(&format!("q{}", i), &random_seq(3000 + i * 500, i as u64)))
+        .collect();
+    let found = exact_hits(&records);
+
+    for (record, hits) in records.iter().zip(&found) {
+        assert!(!hits.is_empty());
+        assert_eq!(*hits, naive_exact_hits(record.seq()));
+    }
+}
+
+#[test]
+fn palindromes_are_reported_on_both_strands() {
+    // ACGTABOX (TACGTA) is its own reverse complement
+    let seq = "GGGGGGGGGGTACGTAGGGGGGGGGG";
+    let records = vec![RecordDesc::new("q", seq)];
+    let found = exact_hits(&records);
+    assert_eq!(found[0], naive_exact_hits(seq));
+
+    let acgtabox: Vec<_> = found[0]
+        .iter()
+        .filter(|(id, ..)| id == "ACGTABOX")
+        .collect();
+    assert_eq!(
+        acgtabox,
+        [
+            &("ACGTABOX".to_string(), 10, 16, Strand::Plus.as_char()),
+            &("ACGTABOX".to_string(), 10, 16, Strand::Minus.as_char()),
+        ]
+    );
+}
+
+#[test]
+fn hits_with_mismatches() {
+    // ARFAT (TGTCTC) with its 5th base changed, at 0-based 5..11
+    let records = vec![RecordDesc::new("q", "GGGGGTGTCACGGGGG")];
+    let arfat = |options: &SearchOptions| {
+        Search::search_elements_with(&PLACE_DB, &records, options).unwrap()[0]
+            .search_descs
+            .iter()
+            .filter(|hit| hit.e_id == "ARFAT")
+            .map(|hit| {
+                (
+                    hit.q_coords.zero_based_half_open(),
+                    hit.mismatches,
+                    hit.mismatch_pos.clone(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert!(arfat(&SearchOptions::default()).is_empty());
+    let one = SearchOptions {
+        max_mismatches: 1,
+        ..Default::default()
+    };
+    assert_eq!(arfat(&one), [((5, 11), 1, vec![9])]);
+    // ARFAT is too short to mismatch
+    let long_only = SearchOptions {
+        mismatch_min_len: 7,
+        ..one
+    };
+    assert!(arfat(&long_only).is_empty());
+}
+
+#[test]
+fn ambiguous_query_bases() {
+    // MYBCORE (CNGTTR) at 4..10 with the `N` of the pattern,
+    // ARFAT (TGTCTC) at 14..20 with an `N` for its C
+    let records = vec![RecordDesc::new("q", "GGGGCNGTTAGGGGTGTNTCGGGG")];
+    let found = |ambiguity| {
+        let options = SearchOptions {
+            ambiguity,
+            ..Default::default()
+        };
+        Search::search_elements_with(&PLACE_DB, &records, &options).unwrap()[0]
+            .search_descs
+            .iter()
+            .filter(|hit| hit.e_id == "MYBCORE" || hit.e_id == "ARFAT")
+            .map(|hit| (hit.e_id, hit.q_coords.start()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(found(AmbiguityPolicy::Strict), [("MYBCORE", 4)]);
+    assert_eq!(
+        found(AmbiguityPolicy::Permissive),
+        [("MYBCORE", 4), ("ARFAT", 14)]
+    );
+    assert!(found(AmbiguityPolicy::SkipN).is_empty());
+}
+
+#[test]
+fn filtered_elements() {
+    let records = vec![RecordDesc::new("q", "AAACCCTAACTGTCTCAATGGATAAGA")];
+    let found = |options: &SearchOptions| {
+        let mut found: Vec<&str> = Search::search_elements_with(&PLACE_DB, &records, options)
+            .unwrap()[0]
+            .search_descs
+            .iter()
+            .map(|hit| hit.e_id)
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    };
+
+    // An exact and an IUPAC element, with and without mismatches
+    let mut options = SearchOptions::default();
+    options.filter.ids = ["ARFAT".to_string(), "MYBCORE".to_string()].into();
+    assert_eq!(found(&options), ["ARFAT", "MYBCORE"]);
+    options.max_mismatches = 1;
+    options.mismatch_min_len = 7;
+    assert_eq!(found(&options), ["ARFAT", "MYBCORE"]);
+
+    let mut options = SearchOptions::default();
+    options.filter.keyword = Some("auxin".to_string());
+    assert!(found(&options).contains(&"ARFAT"));
+    assert!(!found(&options).contains(&"MYBCORE"));
+
+    let mut options = SearchOptions::default();
+    options.filter.ids = ["NOTANELEMENT".to_string()].into();
+    assert!(Search::search_elements_with(&PLACE_DB, &records, &options).is_err());
+}
+
+#[test]
+fn parallel_and_serial_results_agree() {
+    // Enough bases to be searched on several threads
+    let records: Vec<RecordDesc> = (0..24)
+        .map(|i| RecordDesc::new(&format!("q{}", i), &random_seq(4000, 100 + i as u64)))
+        .collect();
+    let key = |result: &SearchResult| -> Vec<(String, String, (usize, usize), char)> {
+        result
+            .search_descs
+            .iter()
+            .map(|hit| {
+                (
+                    hit.q_id.to_string(),
+                    hit.e_id.to_string(),
+                    hit.q_coords.zero_based_half_open(),
+                    hit.q_strand.as_char(),
+                )
+            })
+            .collect()
+    };
+
+    let parallel = Search::search_elements(&records).unwrap();
+    assert_eq!(parallel.len(), records.len());
+    for (record, result) in records.iter().zip(&parallel) {
+        let serial = Search::search_elements_single_seq(record).unwrap();
+        assert_eq!(result.id, record.id());
+        assert_eq!(key(result), key(&serial[0]));
+    }
+}
+
+#[test]
+fn byte_sequences() {
+    let seq = "AAACCCTAACTGTCTCAATGGATAAGA";
+    let record = RecordDesc::from_bytes("q", seq.to_lowercase().as_bytes());
+    assert_eq!(record.seq(), seq);
+    assert_eq!(record.seq_bytes(), seq.as_bytes());
+    assert_eq!(record.len(), seq.len());
+
+    // Lower-case queries give the same hits, at byte offsets
+    let upper = exact_hits(&[RecordDesc::new("q", seq)]);
+    assert_eq!(exact_hits(&[record]), upper);
+    assert!(upper[0].contains(&("ARFAT".to_string(), 10, 16, '+')));
+
+    // Invalid UTF-8 takes one base per byte
+    let record = RecordDesc::from_bytes("q", b"AC\xff\xfeGT");
+    assert_eq!(record.seq(), "ACNNGT");
+    assert_eq!(record.len(), 6);
+
+    assert_eq!(
+        reverse_complement_bytes(b"ACGTRYKMBDHVSWN"),
+        b"NWSBDHVKMRYACGT"
+    );
+}
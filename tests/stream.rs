@@ -0,0 +1,144 @@
+use placecare::db::PLACE_DB;
+use placecare::io::{FastaChunks, RecordDesc, SearchedDesc};
+use placecare::place_search::{Search, SearchOptions};
+
+type Hit = (String, String, (usize, usize), char, Vec<usize>);
+
+fn key(hit: &SearchedDesc<'_>) -> Hit {
+    (
+        hit.q_id.to_string(),
+        hit.e_id.to_string(),
+        hit.q_coords.zero_based_half_open(),
+        hit.q_strand.as_char(),
+        hit.mismatch_pos.clone(),
+    )
+}
+
+fn whole(fasta: &str, options: &SearchOptions) -> Vec<Hit> {
+    let records = RecordDesc::from_string(fasta).unwrap();
+    let mut hits: Vec<Hit> = Search::search_elements_with(&PLACE_DB, &records, options)
+        .unwrap()
+        .iter()
+        .flat_map(|result| result.search_descs.iter().map(key))
+        .collect();
+    hits.sort();
+    hits
+}
+
+fn streamed(fasta: &str, options: &SearchOptions, chunk_len: usize) -> Vec<Hit> {
+    let mut hits = vec![];
+    Search::search_stream_chunked(&PLACE_DB, fasta.as_bytes(), options, chunk_len, |hit| {
+        hits.push(key(&hit));
+        Ok(())
+    })
+    .unwrap();
+    hits.sort();
+    hits
+}
+
+// ARFAT (TGTCTC) and MYBCORE (CNGTTR) in the first record,
+// a record shorter than the overlap of the chunks, and a wrapped one
+const FASTA: &str = "\
+>q1 first
+AAACCCTAACTGTCTCAATGGATAAGA
+>short
+TGTCTC
+>q3
+CACGTGGTTAGTTGACTATAAATAG
+CCAATGTGTCTCACGTGTACGTAGG
+";
+
+#[test]
+fn chunks_cover_the_records() {
+    let chunks: Vec<_> = FastaChunks::new(FASTA.as_bytes(), 7, 3)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let first: Vec<_> = chunks
+        .iter()
+        .filter(|chunk| chunk.record.id() == "q1")
+        .map(|chunk| (chunk.offset, chunk.core_len, chunk.record.seq()))
+        .collect();
+    assert_eq!(
+        first,
+        [
+            (0, 7, "AAACCCTAAC"),
+            (7, 7, "AACTGTCTCA"),
+            (14, 7, "TCAATGGATA"),
+            (21, 6, "ATAAGA"),
+        ]
+    );
+
+    let short: Vec<_> = chunks
+        .iter()
+        .filter(|chunk| chunk.record.id() == "short")
+        .collect();
+    assert_eq!(short.len(), 1);
+    assert_eq!((short[0].offset, short[0].core_len), (0, 6));
+
+    // The wrapped record is read without its line break
+    let q3: String = chunks
+        .iter()
+        .filter(|chunk| chunk.record.id() == "q3")
+        .map(|chunk| &chunk.record.seq()[..chunk.core_len])
+        .collect();
+    assert_eq!(q3.len(), 50);
+}
+
+#[test]
+fn stream_matches_the_whole_search() {
+    let expected = whole(FASTA, &SearchOptions::default());
+    assert!(expected.iter().any(|hit| hit.0 == "short"));
+
+    // Down to chunks of a single base, every hit crosses chunk boundaries
+    for chunk_len in [1, 2, 5, 7, 13, 64, 1 << 20] {
+        assert_eq!(
+            streamed(FASTA, &SearchOptions::default(), chunk_len),
+            expected,
+            "chunks of {}",
+            chunk_len
+        );
+    }
+}
+
+#[test]
+fn invalid_bytes_keep_the_positions() {
+    let mut fasta = b">q\nAAACCC\xff\xffACTGTCTCAATG\n".to_vec();
+    let mut hits = vec![];
+    Search::search_stream_chunked(
+        &PLACE_DB,
+        fasta.as_slice(),
+        &SearchOptions::default(),
+        4,
+        |hit| {
+            hits.push(key(&hit));
+            Ok(())
+        },
+    )
+    .unwrap();
+    hits.sort();
+
+    // The same as the bytes read as `N`
+    fasta[9..11].copy_from_slice(b"NN");
+    assert_eq!(
+        hits,
+        whole(
+            std::str::from_utf8(&fasta).unwrap(),
+            &SearchOptions::default()
+        )
+    );
+    assert!(hits.iter().any(|hit| hit.1 == "ARFAT" && hit.2 == (10, 16)));
+}
+
+#[test]
+fn stream_with_mismatches() {
+    let options = SearchOptions {
+        max_mismatches: 1,
+        mismatch_min_len: 8,
+        ..Default::default()
+    };
+    let expected = whole(FASTA, &options);
+    assert!(expected.iter().any(|hit| !hit.4.is_empty()));
+    for chunk_len in [1, 3, 10] {
+        assert_eq!(streamed(FASTA, &options, chunk_len), expected);
+    }
+}
@@ -0,0 +1,86 @@
+use placecare::Error;
+use placecare::io::RecordDesc;
+use placecare::validate::{Alphabet, GapMode, Sanitizer, ValidationPolicy};
+
+fn sanitizer(policy: ValidationPolicy, rna_to_dna: bool, gaps: GapMode) -> Sanitizer {
+    Sanitizer {
+        policy,
+        rna_to_dna,
+        gaps,
+    }
+}
+
+#[test]
+fn classify_alphabets() {
+    assert_eq!(Alphabet::classify(b"ACGT"), Alphabet::Dna);
+    assert_eq!(Alphabet::classify(b"ACGU"), Alphabet::Rna);
+    assert_eq!(Alphabet::classify(b"ACGTNRY"), Alphabet::Iupac);
+    assert_eq!(Alphabet::classify(b"MKVLQE*"), Alphabet::Invalid);
+    assert_eq!(Alphabet::classify(b""), Alphabet::Dna);
+}
+
+#[test]
+fn policies() {
+    let record = RecordDesc::new("q", "ACGU1T");
+
+    let error = sanitizer(ValidationPolicy::Error, false, GapMode::Keep);
+    match error.sanitize(&record) {
+        Err(Error::InvalidSequence { id, message }) => {
+            assert_eq!(id, "q");
+            assert_eq!(
+                message,
+                "invalid sequence, 2 invalid character(s) at 4 (U), 5 (1)"
+            );
+        }
+        other => panic!("{:?}", other.map(|_| ())),
+    }
+
+    let warn = sanitizer(ValidationPolicy::Warn, false, GapMode::Keep);
+    let (records, report) = warn.sanitize(&record).unwrap();
+    assert_eq!(records[0].seq(), "ACGU1T");
+    assert_eq!(report.unwrap().invalid, [(4, b'U'), (5, b'1')]);
+
+    let clean = sanitizer(ValidationPolicy::Clean, true, GapMode::Keep);
+    let (records, report) = clean.sanitize(&record).unwrap();
+    assert_eq!(records[0].seq(), "ACGTNT");
+    assert_eq!(report.unwrap().invalid, [(5, b'1')]);
+
+    let (records, report) = error.sanitize(&RecordDesc::new("ok", "ACGTN")).unwrap();
+    assert_eq!(records[0].seq(), "ACGTN");
+    assert!(report.is_none());
+}
+
+#[test]
+fn gaps() {
+    let record = RecordDesc::new("row", "AC--GT.A").with_anchor(5);
+
+    let (records, report) = sanitizer(ValidationPolicy::Error, false, GapMode::Strip)
+        .sanitize(&record)
+        .unwrap();
+    assert!(report.is_none());
+    assert_eq!(records[0].seq(), "ACGTA");
+    assert_eq!(records[0].anchor(), Some(3));
+
+    let (records, _) = sanitizer(ValidationPolicy::Error, false, GapMode::Split)
+        .sanitize(&record)
+        .unwrap();
+    let pieces: Vec<(&str, &str, Option<isize>)> = records
+        .iter()
+        .map(|r| (r.id(), r.seq(), r.anchor()))
+        .collect();
+    assert_eq!(
+        pieces,
+        [
+            ("row:1-2", "AC", Some(5)),
+            ("row:5-6", "GT", Some(1)),
+            ("row:8-8", "A", Some(-2)),
+        ]
+    );
+
+    // Kept gaps are invalid characters
+    assert!(
+        sanitizer(ValidationPolicy::Error, false, GapMode::Keep)
+            .sanitize(&record)
+            .is_err()
+    );
+}